@@ -0,0 +1,222 @@
+use {
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+const BEAM_COLOR: (u8, u8, u8) = (255, 92, 140);
+const CORE_COLOR: (u8, u8, u8) = (255, 255, 255);
+/// How much of the beam’s width its bright core takes up.
+const CORE_WIDTH_MUL: f32 = 0.4;
+/// The width of a beam while it is warming up, when it is only a warning.
+const WARM_UP_WIDTH: f32 = 2.0;
+const WARM_UP_ALPHA: f32 = 0.6;
+/// An active beam only hurts every so often, rather than every tick the ship spends in it.
+const DAMAGE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A continuous laser fired by an enemy. A beam starts off thin and harmless while it warms up,
+/// then fires at full width (turning around its origin, if it turns at all) and finally fades out.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Beam {
+    origin: crate::Point,
+    /// The direction the beam points in, in radians.
+    angle: f32,
+    /// How fast the beam turns while it is active, in radians per second. Positive values turn
+    /// clockwise on screen.
+    turn_rate: f32,
+    length: f32,
+    width: f32,
+    warm_up: Duration,
+    active: Duration,
+    fade_out: Duration,
+    age: Duration,
+    time_until_damage: Duration,
+    is_damaging: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BeamPhase {
+    WarmUp,
+    Active,
+    FadeOut,
+    Finished,
+}
+
+impl Beam {
+    pub fn new(
+        origin: crate::Point,
+        angle: f32,
+        length: f32,
+        width: f32,
+        warm_up: Duration,
+        active: Duration,
+        fade_out: Duration,
+    ) -> Self {
+        Self {
+            origin,
+            angle,
+            turn_rate: 0.0,
+            length,
+            width,
+            warm_up,
+            active,
+            fade_out,
+            age: Duration::from_millis(0),
+            time_until_damage: Duration::from_millis(0),
+            is_damaging: false,
+        }
+    }
+
+    pub fn with_turn_rate(mut self, turn_rate: f32) -> Self {
+        self.turn_rate = turn_rate;
+        self
+    }
+
+    pub fn phase(&self) -> BeamPhase {
+        if self.age < self.warm_up {
+            BeamPhase::WarmUp
+        } else if self.age < self.warm_up + self.active {
+            BeamPhase::Active
+        } else if self.age < self.warm_up + self.active + self.fade_out {
+            BeamPhase::FadeOut
+        } else {
+            BeamPhase::Finished
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase() == BeamPhase::Finished
+    }
+
+    /// Moves the beam along with whatever is firing it, and turns it if it is active.
+    pub fn update(&mut self, origin: crate::Point, delta_time: Duration) {
+        self.origin = origin;
+        self.age += delta_time;
+
+        self.is_damaging = false;
+
+        if self.phase() == BeamPhase::Active {
+            self.angle += self.turn_rate * delta_time.as_secs_f32();
+
+            self.time_until_damage = self.time_until_damage.saturating_sub(delta_time);
+
+            if self.time_until_damage == Duration::from_millis(0) {
+                self.is_damaging = true;
+                self.time_until_damage = DAMAGE_INTERVAL;
+            }
+        }
+    }
+
+    /// Whether the beam hurts something with the given hitbox this tick.
+    pub fn hits(&self, hitbox: graphics::Rect) -> bool {
+        self.is_damaging && self.overlaps(hitbox)
+    }
+
+    /// Whether the hitbox is inside the beam while it is firing, even on ticks when the beam
+    /// doesn’t hurt.
+    pub fn covers(&self, hitbox: graphics::Rect) -> bool {
+        self.phase() == BeamPhase::Active && self.overlaps(hitbox)
+    }
+
+    /// Checks whether the beam’s full-width rectangle overlaps the hitbox, by looking for a gap
+    /// between them along each of their axes (the separating axis theorem).
+    fn overlaps(&self, hitbox: graphics::Rect) -> bool {
+        let beam_corners = self.corners(self.width);
+        let hitbox_corners = [
+            crate::Point::new(hitbox.x, hitbox.y),
+            crate::Point::new(hitbox.x + hitbox.w, hitbox.y),
+            crate::Point::new(hitbox.x + hitbox.w, hitbox.y + hitbox.h),
+            crate::Point::new(hitbox.x, hitbox.y + hitbox.h),
+        ];
+
+        let direction = self.direction();
+        let axes = [
+            crate::Vector::new(1.0, 0.0),
+            crate::Vector::new(0.0, 1.0),
+            direction,
+            crate::Vector::new(-direction.y, direction.x),
+        ];
+
+        let project = |corners: &[crate::Point], axis: crate::Vector| {
+            corners
+                .iter()
+                .map(|corner| corner.coords.dot(&axis))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| {
+                    (min.min(x), max.max(x))
+                })
+        };
+
+        axes.iter().all(|&axis| {
+            let (beam_min, beam_max) = project(&beam_corners, axis);
+            let (hitbox_min, hitbox_max) = project(&hitbox_corners, axis);
+
+            beam_min <= hitbox_max && hitbox_min <= beam_max
+        })
+    }
+
+    fn direction(&self) -> crate::Vector {
+        crate::Vector::new(self.angle.cos(), self.angle.sin())
+    }
+
+    /// The corners of the beam’s rectangle if it were `width` wide, going around the rectangle.
+    fn corners(&self, width: f32) -> [crate::Point; 4] {
+        let direction = self.direction();
+        let half_width = crate::Vector::new(-direction.y, direction.x) * width / 2.0;
+        let end = self.origin + direction * self.length;
+
+        [
+            self.origin + half_width,
+            end + half_width,
+            end - half_width,
+            self.origin - half_width,
+        ]
+    }
+
+    pub fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let (r, g, b) = BEAM_COLOR;
+        let mut builder = graphics::MeshBuilder::new();
+
+        match self.phase() {
+            BeamPhase::WarmUp => {
+                let color = graphics::Color::from_rgba(r, g, b, (WARM_UP_ALPHA * 255.0) as u8);
+                let end = self.origin + self.direction() * self.length;
+
+                builder.line(&[self.origin, end], WARM_UP_WIDTH, color)?;
+            }
+            BeamPhase::Active | BeamPhase::FadeOut => {
+                let alpha = if self.phase() == BeamPhase::FadeOut {
+                    let time_left = (self.warm_up + self.active + self.fade_out) - self.age;
+                    time_left.as_secs_f32() / self.fade_out.as_secs_f32()
+                } else {
+                    1.0
+                };
+                let (core_r, core_g, core_b) = CORE_COLOR;
+
+                builder.polygon(
+                    graphics::DrawMode::fill(),
+                    &self.corners(self.width),
+                    graphics::Color::from_rgba(r, g, b, (alpha * 255.0) as u8),
+                )?;
+                builder.polygon(
+                    graphics::DrawMode::fill(),
+                    &self.corners(self.width * CORE_WIDTH_MUL),
+                    graphics::Color::from_rgba(core_r, core_g, core_b, (alpha * 255.0) as u8),
+                )?;
+            }
+            BeamPhase::Finished => return Ok(()),
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, (crate::Point::new(0.0, 0.0),))
+    }
+}
+
+impl crate::Damage for Beam {
+    fn damage(&self) -> u16 {
+        1
+    }
+
+    fn damage_kind(&self) -> crate::DamageKind {
+        crate::DamageKind::Energy
+    }
+}
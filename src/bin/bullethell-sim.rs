@@ -0,0 +1,232 @@
+//! Plays runs of the game without a window, as fast as they can be simulated, and prints
+//! statistics about each one as a line of JSON. This is for balancing, where thousands of runs are
+//! needed to tell how hard a pattern really is.
+//!
+//! With `--bot`, a bot plays the runs, and every time it gets hit is reported. The bot dodges as
+//! well as it can and never bombs, so the patterns it gets hit by are likely to be impossible.
+
+use {serde::Serialize, std::path::PathBuf};
+
+const USAGE: &str = "usage: bullethell-sim [--seed <seed>] [--runs <count>] [--ticks <count>] \
+[--difficulty <difficulty>] [--replay <file> | --bot]";
+/// How long a run is played for at most, unless told otherwise: ten minutes, which is longer than
+/// the whole game takes.
+const DEFAULT_TICKS: u32 = 10 * 60 * game_test::TICKS_PER_SECOND;
+
+/// What to simulate, as given on the command line.
+struct Options {
+    seed: u64,
+    runs: u64,
+    ticks: u32,
+    difficulty: game_test::Difficulty,
+    replay: Option<PathBuf>,
+    bot: bool,
+}
+
+impl Options {
+    /// Reads the options from the command line arguments (without the program name).
+    fn from_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut options = Self {
+            seed: rand::random(),
+            runs: 1,
+            ticks: DEFAULT_TICKS,
+            difficulty: Default::default(),
+            replay: None,
+            bot: false,
+        };
+
+        while let Some(flag) = args.next() {
+            if flag == "--bot" {
+                options.bot = true;
+                continue;
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("missing a value for {}", flag))?;
+
+            match flag.as_str() {
+                "--seed" => options.seed = value.parse()?,
+                "--runs" => options.runs = value.parse()?,
+                "--ticks" => options.ticks = value.parse()?,
+                "--difficulty" => {
+                    options.difficulty = game_test::Difficulty::ALL
+                        .iter()
+                        .copied()
+                        .find(|difficulty| difficulty.name().eq_ignore_ascii_case(&value))
+                        .ok_or_else(|| anyhow::anyhow!("there is no ‘{}’ difficulty", value))?
+                }
+                "--replay" => options.replay = Some(value.into()),
+                _ => anyhow::bail!("unexpected argument ‘{}’", flag),
+            }
+        }
+
+        if options.replay.is_some() && options.runs != 1 {
+            anyhow::bail!("a replay can only be played once");
+        }
+
+        if options.replay.is_some() && options.bot {
+            anyhow::bail!("a replay can’t be played by the bot");
+        }
+
+        Ok(options)
+    }
+}
+
+/// How a simulated run went.
+#[derive(Serialize)]
+struct Report {
+    seed: u64,
+    ticks: u32,
+    damage_taken: u32,
+    bullets_spawned: u32,
+    attacks_timed_out: u32,
+    /// How long the run lasted, in seconds of game time.
+    survival_time: f32,
+    /// `died`, `won`, or `unfinished` if the run was still going when the ticks ran out.
+    outcome: &'static str,
+    score: u32,
+    /// Every time the bot got hit, if the bot was playing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bot_hits: Option<Vec<Hit>>,
+}
+
+/// Where the bot got hit, to find the pattern that did it.
+#[derive(Serialize)]
+struct Hit {
+    tick: u32,
+    stage: String,
+    /// How far into the stage the hit was, in seconds.
+    stage_time: f32,
+    /// The boss attack that was being fought, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    boss_attack: Option<&'static str>,
+}
+
+impl Hit {
+    fn new(game: &game_test::Game) -> Self {
+        Self {
+            tick: game.tick(),
+            stage: game.stage().name().to_string(),
+            stage_time: game.stage().time().as_secs_f32(),
+            boss_attack: game
+                .enemies()
+                .iter()
+                .find_map(|enemy| enemy.attempt())
+                .map(|attempt| attempt.attack().name),
+        }
+    }
+}
+
+impl Report {
+    fn new(game: &game_test::Game) -> Self {
+        let stats = game.stats();
+
+        Self {
+            seed: game.seed(),
+            ticks: game.tick(),
+            damage_taken: stats.damage_taken,
+            bullets_spawned: stats.bullets_spawned,
+            attacks_timed_out: stats.attacks_timed_out,
+            survival_time: game.time().as_secs_f32(),
+            outcome: match game.outcome() {
+                Some(game_test::Outcome::Died) => "died",
+                Some(game_test::Outcome::Won) => "won",
+                None => "unfinished",
+            },
+            score: game.score(),
+            bot_hits: None,
+        }
+    }
+}
+
+/// Where the players’ inputs come from.
+enum Pilot {
+    /// Players do nothing once they run out of recorded inputs.
+    Replay(game_test::Replay),
+    Idle,
+    Bot,
+}
+
+/// Plays a run until it ends or `ticks` have gone by. Replays are checked as they go, so that one
+/// that has gone out of step with the run it was recorded from fails rather than reporting on
+/// some other run.
+fn simulate(mut game: game_test::Game, pilot: &Pilot, ticks: u32) -> anyhow::Result<Report> {
+    let screen = game_test::PLAYFIELD;
+    let idle = vec![Default::default(); game.players().len()];
+    let bots: Vec<_> = (0..game.players().len()).map(game_test::Bot::new).collect();
+    let mut bot_hits = vec![];
+
+    while game.outcome().is_none() && game.tick() < ticks {
+        let inputs = match pilot {
+            Pilot::Replay(replay) => {
+                replay.check(&game)?;
+                replay
+                    .inputs
+                    .get(game.tick() as usize)
+                    .unwrap_or(&idle)
+                    .clone()
+            }
+            Pilot::Idle => idle.clone(),
+            Pilot::Bot => bots.iter().map(|bot| bot.input(&game, screen)).collect(),
+        };
+
+        let damage_taken = game.stats().damage_taken;
+        game.update(&inputs, screen);
+
+        if game.stats().damage_taken > damage_taken {
+            bot_hits.push(Hit::new(&game));
+        }
+    }
+
+    let mut report = Report::new(&game);
+
+    if let Pilot::Bot = pilot {
+        report.bot_hits = Some(bot_hits);
+    }
+
+    Ok(report)
+}
+
+fn run(options: Options) -> anyhow::Result<()> {
+    if let Some(path) = &options.replay {
+        let replay = game_test::Replay::load(path)?;
+        let game = replay.start();
+        let report = simulate(game, &Pilot::Replay(replay), options.ticks)?;
+        println!("{}", serde_json::to_string(&report)?);
+
+        return Ok(());
+    }
+
+    let pilot = if options.bot { Pilot::Bot } else { Pilot::Idle };
+    let setup = game_test::RunSetup {
+        difficulty: options.difficulty,
+        ..Default::default()
+    };
+
+    // Each run after the first is played with the next seed along, so that a batch of runs can be
+    // picked up again from any one of them.
+    for run in 0..options.runs {
+        let seed = options.seed.wrapping_add(run);
+        let game = game_test::Game::new(seed, setup, game_test::PLAYFIELD);
+        let report = simulate(game, &pilot, options.ticks)?;
+        println!("{}", serde_json::to_string(&report)?);
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(error) = run(options) {
+        eprintln!("Failed to simulate the run: {}", error);
+        std::process::exit(1);
+    }
+}
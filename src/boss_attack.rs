@@ -0,0 +1,144 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, path::Path, time::Duration},
+};
+
+const CAPTURE_HISTORY_FILE: &str = "captures.ron";
+
+/// One of a boss’s named attacks, which makes up a phase of the fight.
+#[derive(Clone, Copy)]
+pub struct BossAttack {
+    pub name: &'static str,
+    /// How long the player has to get through the attack before it ends on its own.
+    pub time_limit: Duration,
+}
+
+/// Boss attacks are saved by name, and found again among the bosses’ attacks when loaded.
+impl Serialize for BossAttack {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for BossAttack {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let name = String::deserialize(deserializer)?;
+
+        crate::EnemyKind::ALL
+            .iter()
+            .flat_map(|kind| kind.boss_attacks())
+            .find(|attack| attack.name == name)
+            .copied()
+            .ok_or_else(|| D::Error::custom(format!("there is no boss attack called ‘{}’", name)))
+    }
+}
+
+/// A boss attack in progress. The attack is captured if the player gets through it before it
+/// times out without being hit or using a bomb.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AttackAttempt {
+    attack: BossAttack,
+    time_left: Duration,
+    is_capturable: bool,
+}
+
+impl AttackAttempt {
+    pub fn new(attack: BossAttack) -> Self {
+        Self {
+            attack,
+            time_left: attack.time_limit,
+            is_capturable: true,
+        }
+    }
+
+    pub fn attack(&self) -> BossAttack {
+        self.attack
+    }
+
+    pub fn time_left(&self) -> Duration {
+        self.time_left
+    }
+
+    /// Counts down the time limit. Running out of time means the attack can no longer be
+    /// captured.
+    pub fn update(&mut self, delta_time: Duration) {
+        self.time_left = self.time_left.saturating_sub(delta_time);
+
+        if self.is_timed_out() {
+            self.is_capturable = false;
+        }
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.time_left == Duration::from_millis(0)
+    }
+
+    /// Rules out capturing the attack, because the player was hit or used a bomb.
+    pub fn spoil(&mut self) {
+        self.is_capturable = false;
+    }
+
+    pub fn is_capturable(&self) -> bool {
+        self.is_capturable
+    }
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CaptureRecord {
+    pub attempts: u32,
+    pub captures: u32,
+}
+
+/// How the player has fared against every boss attack they have faced, on each difficulty. The
+/// history is kept between runs in the user data directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct CaptureHistory {
+    records: HashMap<crate::Difficulty, HashMap<String, CaptureRecord>>,
+}
+
+impl CaptureHistory {
+    /// Loads the history from `dir`, starting a new one if there isn’t one there yet.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(CAPTURE_HISTORY_FILE);
+
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+
+        Ok(ron::de::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, dir: &Path) -> anyhow::Result<()> {
+        let history = ron::ser::to_string_pretty(self, Default::default())?;
+
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(CAPTURE_HISTORY_FILE), history)?;
+
+        Ok(())
+    }
+
+    pub fn record(&mut self, difficulty: crate::Difficulty, attack: BossAttack, is_captured: bool) {
+        let record = self
+            .records
+            .entry(difficulty)
+            .or_default()
+            .entry(attack.name.to_string())
+            .or_default();
+
+        record.attempts += 1;
+
+        if is_captured {
+            record.captures += 1;
+        }
+    }
+
+    pub fn get(&self, difficulty: crate::Difficulty, attack: BossAttack) -> CaptureRecord {
+        self.records
+            .get(&difficulty)
+            .and_then(|records| records.get(attack.name))
+            .copied()
+            .unwrap_or_default()
+    }
+}
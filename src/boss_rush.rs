@@ -0,0 +1,230 @@
+use {
+    crate::{EnemyKind, StageEvent},
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, path::Path, time::Duration},
+};
+
+const LEADERBOARD_FILE: &str = "boss_rush.ron";
+const LEADERBOARD_SIZE: usize = 10;
+const FIRST_BOSS_DELAY: Duration = Duration::from_secs(3);
+/// How long the results card is shown for between bosses.
+const RESULTS_DURATION: Duration = Duration::from_secs(4);
+
+/// Runs boss rush mode, sending in every boss in the game one after another, starting from the
+/// final boss and working back. Each fight is followed by a break with a card showing how it
+/// went.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BossRush {
+    /// The event that starts each boss, in the order they are fought.
+    bosses: Vec<StageEvent>,
+    next_boss: usize,
+    time: Duration,
+    time_until_boss: Duration,
+    /// How the boss being fought is going, if there is one.
+    fight: Option<BossResult>,
+    /// How the last boss fight went, while its card is being shown.
+    last_result: Option<BossResult>,
+    bosses_defeated: u32,
+}
+
+/// How a boss fight in a boss rush went.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct BossResult {
+    pub boss: EnemyKind,
+    pub time: Duration,
+    pub attacks: u32,
+    pub captures: u32,
+    /// Whether the boss ran out of time on its last attack and got away, rather than being
+    /// defeated.
+    pub has_escaped: bool,
+}
+
+impl BossRush {
+    /// Lines up every mid-boss and boss in the given stages. Bosses that turn up more than once
+    /// are only fought the once.
+    pub fn new(stages: &[crate::Stage]) -> Self {
+        let mut bosses: Vec<StageEvent> = vec![];
+
+        for stage in stages.iter().rev() {
+            let mut events: Vec<_> = stage.events.iter().collect();
+            events.sort_by(|a, b| b.at.total_cmp(&a.at));
+
+            for timed_event in events {
+                let (enemy, pos) = match timed_event.event {
+                    StageEvent::StartMidBoss { enemy, pos, .. }
+                    | StageEvent::StartBoss { enemy, pos } => (enemy, pos),
+                    _ => continue,
+                };
+
+                let is_new = !bosses.iter().any(|boss| {
+                    matches!(boss, StageEvent::StartBoss { enemy: other, .. } if *other == enemy)
+                });
+
+                // There is no timeline to hold, so mid-bosses are fought to the end like bosses.
+                if is_new {
+                    bosses.push(StageEvent::StartBoss { enemy, pos });
+                }
+            }
+        }
+
+        Self {
+            bosses,
+            next_boss: 0,
+            time: Duration::from_millis(0),
+            time_until_boss: FIRST_BOSS_DELAY,
+            fight: None,
+            last_result: None,
+            bosses_defeated: 0,
+        }
+    }
+
+    /// How long the boss rush has been going.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Advances by a tick, returning the next boss if it is time for it to arrive.
+    pub fn update(&mut self, delta_time: Duration, is_boss_alive: bool) -> Vec<StageEvent> {
+        self.time += delta_time;
+
+        match &mut self.fight {
+            Some(fight) if is_boss_alive => {
+                fight.time += delta_time;
+                return vec![];
+            }
+            Some(fight) => {
+                if !fight.has_escaped {
+                    self.bosses_defeated += 1;
+                }
+
+                self.last_result = self.fight.take();
+                self.time_until_boss = RESULTS_DURATION;
+                return vec![];
+            }
+            None => (),
+        }
+
+        self.time_until_boss = self.time_until_boss.saturating_sub(delta_time);
+
+        if self.time_until_boss > Duration::from_millis(0) || self.next_boss == self.bosses.len() {
+            return vec![];
+        }
+
+        let event = self.bosses[self.next_boss].clone();
+        self.next_boss += 1;
+        self.last_result = None;
+        self.fight = match event {
+            StageEvent::StartBoss { enemy, .. } => Some(BossResult {
+                boss: enemy,
+                time: Duration::from_millis(0),
+                attacks: 0,
+                captures: 0,
+                has_escaped: false,
+            }),
+            _ => unreachable!(),
+        };
+
+        vec![event]
+    }
+
+    /// Counts a boss attack towards the results of the fight in progress.
+    pub fn record_attack(&mut self, is_captured: bool) {
+        if let Some(fight) = &mut self.fight {
+            fight.attacks += 1;
+
+            if is_captured {
+                fight.captures += 1;
+            }
+        }
+    }
+
+    /// Notes that the boss being fought ran out of time and is getting away.
+    pub fn record_escape(&mut self) {
+        if let Some(fight) = &mut self.fight {
+            fight.has_escaped = true;
+        }
+    }
+
+    /// How the last boss fight went, while its results card is up.
+    pub fn last_result(&self) -> Option<BossResult> {
+        self.last_result
+    }
+
+    pub fn bosses_defeated(&self) -> u32 {
+        self.bosses_defeated
+    }
+
+    pub fn boss_count(&self) -> usize {
+        self.bosses.len()
+    }
+
+    /// Whether every boss has been defeated and the last results card has been shown.
+    pub fn is_finished(&self) -> bool {
+        self.next_boss == self.bosses.len()
+            && self.fight.is_none()
+            && self.time_until_boss == Duration::from_millis(0)
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    pub bosses_defeated: u32,
+    /// How long the run lasted, in seconds.
+    pub time: f32,
+}
+
+/// The best boss rush runs on each difficulty, kept apart from the rest of the game’s scores.
+/// The leaderboard is kept between runs in the user data directory.
+#[derive(Default, Serialize, Deserialize)]
+pub struct BossRushLeaderboard {
+    entries: HashMap<crate::Difficulty, Vec<LeaderboardEntry>>,
+}
+
+impl BossRushLeaderboard {
+    /// Loads the leaderboard from `dir`, starting a new one if there isn’t one there yet.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(LEADERBOARD_FILE);
+
+        if !path.exists() {
+            return Ok(Default::default());
+        }
+
+        Ok(ron::de::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, dir: &Path) -> anyhow::Result<()> {
+        let leaderboard = ron::ser::to_string_pretty(self, Default::default())?;
+
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(LEADERBOARD_FILE), leaderboard)?;
+
+        Ok(())
+    }
+
+    /// Adds a run to the leaderboard, returning where it placed (counting from 0), or `None` if it
+    /// didn’t make it on.
+    pub fn add(&mut self, difficulty: crate::Difficulty, entry: LeaderboardEntry) -> Option<usize> {
+        let entries = self.entries.entry(difficulty).or_default();
+        let place = entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(entries.len());
+
+        if place >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        entries.insert(place, entry);
+        entries.truncate(LEADERBOARD_SIZE);
+
+        Some(place)
+    }
+
+    /// The runs on the leaderboard for a difficulty, from best to worst.
+    pub fn entries(&self, difficulty: crate::Difficulty) -> &[LeaderboardEntry] {
+        self.entries
+            .get(&difficulty)
+            .map_or(&[], |entries| entries.as_slice())
+    }
+}
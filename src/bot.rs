@@ -0,0 +1,180 @@
+use ggez::graphics;
+
+/// A computer player, which flies a ship by pressing the same buttons a person would. Every tick
+/// it looks a little way ahead at where the bullets are going, tries holding down each way of
+/// moving, and goes with whichever keeps the ship clear of them the longest, while staying under
+/// the enemies to shoot at them. It never bombs, so if it gets hit, it found no way through.
+pub struct Bot {
+    player: usize,
+}
+
+/// How a way of moving works out over the ticks the bot looks ahead.
+#[derive(Clone, Copy)]
+struct Plan {
+    /// How many ticks the ship gets through before it is hit.
+    ticks_clear: usize,
+    /// How close the ship comes to bullets along the way, with nearer misses and sooner ones
+    /// counting for more.
+    danger: f32,
+    /// How far the ship ends up from where the bot wants to be, as a fraction of the screen.
+    distance_from_home: f32,
+}
+
+impl Plan {
+    /// How many ticks of danger are worth moving across the whole screen for.
+    const HOME_WEIGHT: f32 = 0.5;
+
+    fn cost(&self) -> f32 {
+        self.danger + self.distance_from_home * Self::HOME_WEIGHT
+    }
+
+    /// Whether this plan is better than the other one. Not getting hit for longer always wins.
+    fn is_better_than(&self, other: &Plan) -> bool {
+        match self.ticks_clear.cmp(&other.ticks_clear) {
+            std::cmp::Ordering::Equal => self.cost() < other.cost(),
+            ordering => ordering == std::cmp::Ordering::Greater,
+        }
+    }
+}
+
+impl Bot {
+    const LOOKAHEAD_TICKS: usize = 20;
+    /// How much room the bot leaves around its hitbox, to allow for bullets going slightly
+    /// differently to how they were forecast.
+    const MARGIN: f32 = 3.0;
+    /// Bullets further away than this from the middle of the ship don’t worry the bot.
+    const DANGER_RADIUS: f32 = 48.0;
+    /// How far down the screen the bot likes to sit, as a fraction of the screen’s height.
+    const HOME_HEIGHT: f32 = 0.8;
+    /// Which of up, down, left and right to hold for each way of moving.
+    const DIRECTIONS: [(bool, bool, bool, bool); 9] = [
+        (false, false, false, false),
+        (true, false, false, false),
+        (false, true, false, false),
+        (false, false, true, false),
+        (false, false, false, true),
+        (true, false, true, false),
+        (true, false, false, true),
+        (false, true, true, false),
+        (false, true, false, true),
+    ];
+
+    /// Creates a bot that plays as the given player (counting from 0).
+    pub fn new(player: usize) -> Self {
+        Self { player }
+    }
+
+    /// Decides what to press this tick.
+    pub fn input(&self, game: &crate::Game, screen: graphics::Rect) -> crate::PlayerInput {
+        let player = match game.players().get(self.player) {
+            Some(player) if player.is_playing() => player,
+            _ => return Default::default(),
+        };
+
+        let forecast = game.forecast(Self::LOOKAHEAD_TICKS, screen);
+        let home = Self::home(game, player, screen);
+        let mut best: Option<(Plan, crate::PlayerInput)> = None;
+
+        for input in Self::candidates() {
+            let plan = Self::plan(player.ship(), &input, &forecast, home, screen);
+
+            if best.map_or(true, |(best_plan, _)| plan.is_better_than(&best_plan)) {
+                best = Some((plan, input));
+            }
+        }
+
+        best.map_or_else(Default::default, |(_, input)| input)
+    }
+
+    /// Every way of moving, always shooting. Standing still comes first, so that the bot
+    /// doesn’t fidget when it has nothing better to do.
+    fn candidates() -> impl Iterator<Item = crate::PlayerInput> {
+        [false, true].iter().flat_map(|&focus| {
+            Self::DIRECTIONS
+                .iter()
+                .map(move |&(up, down, left, right)| crate::PlayerInput {
+                    up,
+                    down,
+                    left,
+                    right,
+                    shoot: true,
+                    focus,
+                    ..Default::default()
+                })
+        })
+    }
+
+    /// Where the bot would like the ship to be: near the bottom of the screen, underneath the
+    /// enemy nearest to it from side to side.
+    fn home(game: &crate::Game, player: &crate::Player, screen: graphics::Rect) -> crate::Point {
+        use crate::Position;
+
+        let ship_x = player.ship().pos().x;
+        let x = game
+            .enemies()
+            .iter()
+            .map(|enemy| {
+                let hitbox = enemy.hitbox();
+                hitbox.x + hitbox.w / 2.0
+            })
+            .filter(|x| *x >= screen.x && *x <= screen.x + screen.w)
+            .min_by(|a, b| (a - ship_x).abs().total_cmp(&(b - ship_x).abs()))
+            .unwrap_or(screen.x + screen.w / 2.0);
+
+        crate::Point::new(x, screen.y + screen.h * Self::HOME_HEIGHT)
+    }
+
+    /// Works out how holding down the given input for the whole forecast would go.
+    fn plan(
+        ship: &crate::Ship,
+        input: &crate::PlayerInput,
+        forecast: &crate::Forecast,
+        home: crate::Point,
+        screen: graphics::Rect,
+    ) -> Plan {
+        use crate::{Hitbox, Position};
+
+        let mut ship = ship.clone();
+        let mut ticks_clear = 0;
+        let mut danger = 0.0;
+
+        for (tick, (bullets, beams)) in forecast.bullets.iter().zip(&forecast.beams).enumerate() {
+            crate::steer(&mut ship, input, screen);
+
+            let hitbox = ship.hitbox();
+            let padded = graphics::Rect::new(
+                hitbox.x - Self::MARGIN,
+                hitbox.y - Self::MARGIN,
+                hitbox.w + Self::MARGIN * 2.0,
+                hitbox.h + Self::MARGIN * 2.0,
+            );
+
+            let is_hit = bullets.iter().any(|bullet| bullet.overlaps(&padded))
+                || beams.iter().any(|beam| beam.covers(padded));
+
+            if is_hit {
+                break;
+            }
+
+            ticks_clear += 1;
+
+            let center = crate::Point::new(hitbox.x + hitbox.w / 2.0, hitbox.y + hitbox.h / 2.0);
+
+            for bullet in bullets {
+                let bullet_center =
+                    crate::Point::new(bullet.x + bullet.w / 2.0, bullet.y + bullet.h / 2.0);
+                let closeness = 1.0 - (bullet_center - center).norm() / Self::DANGER_RADIUS;
+
+                if closeness > 0.0 {
+                    danger += closeness * closeness / (tick + 1) as f32;
+                }
+            }
+        }
+
+        Plan {
+            ticks_clear,
+            danger,
+            distance_from_home: (ship.pos() - home).norm() / screen.w.max(screen.h),
+        }
+    }
+}
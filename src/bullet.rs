@@ -1,20 +1,36 @@
-use {crate::Sprite, ggez::graphics};
+use {
+    crate::Hitbox,
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Bullet {
     pos: crate::Point,
     velocity: crate::Vector,
-    sprite_cache: graphics::Image,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
+    shape: crate::BulletShape,
+    /// The tint, as red, green, blue and alpha, since ggez’s colours can’t be saved as they are.
+    color: [f32; 4],
+    behaviours: Vec<crate::BulletBehaviour>,
+    edge_policy: crate::EdgePolicy,
+    age: Duration,
+    has_split: bool,
+    /// The enemy that fired the bullet, if it should go when that enemy does.
+    owner: Option<crate::EnemyId>,
+    has_grazed: bool,
 }
 
-impl<'a> Bullet {
-    const IMG_DATA: &'a [u8] = include_bytes!("bullet.png");
-
-    pub fn new(pos: crate::Point, ctx: &mut ggez::Context) -> Self {
-        use rand::Rng;
+impl Bullet {
+    /// The red that bullets were before they could be tinted.
+    const DEFAULT_COLOR: graphics::Color = graphics::Color::new(1.0, 0.45, 0.35, 1.0);
+    /// The shape that bullets split into.
+    const SPLIT_SHAPE: crate::BulletShape = crate::BulletShape::SmallRound;
 
+    pub fn new(pos: crate::Point, rng: &mut impl rand::Rng) -> Self {
         let velocity = {
-            let mut rng = rand::thread_rng();
-
             let mut rand_vector =
                 crate::Vector::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0));
 
@@ -25,12 +41,219 @@ impl<'a> Bullet {
             rand_vector
         };
 
+        Self::with_velocity(pos, velocity)
+    }
+
+    pub fn with_velocity(pos: crate::Point, velocity: crate::Vector) -> Self {
+        let shape = crate::BulletShape::default();
+
         Self {
             pos,
             velocity,
-            sprite_cache: Self::sprite(Self::IMG_DATA, ctx).unwrap(),
+            sprite_cache: crate::SpriteCache::new(shape.img_data()),
+            shape,
+            color: Self::DEFAULT_COLOR.into(),
+            behaviours: vec![],
+            edge_policy: Default::default(),
+            age: Duration::from_millis(0),
+            has_split: false,
+            owner: None,
+            has_grazed: false,
+        }
+    }
+
+    pub fn with_shape(mut self, shape: crate::BulletShape) -> Self {
+        self.sprite_cache = crate::SpriteCache::new(shape.img_data());
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_color(mut self, color: graphics::Color) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    pub fn with_behaviour(mut self, behaviour: crate::BulletBehaviour) -> Self {
+        self.behaviours.push(behaviour);
+        self
+    }
+
+    pub fn with_owner(mut self, owner: crate::EnemyId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn owner(&self) -> Option<crate::EnemyId> {
+        self.owner
+    }
+
+    /// Notes that the bullet has brushed past the ship, returning whether this is the first time
+    /// it has. Each bullet only counts as a graze once.
+    pub fn graze(&mut self) -> bool {
+        !std::mem::replace(&mut self.has_grazed, true)
+    }
+
+    pub fn with_edge_policy(mut self, edge_policy: crate::EdgePolicy) -> Self {
+        self.edge_policy = edge_policy;
+        self
+    }
+
+    /// Applies the bullet’s behaviours for a tick, returning any bullets it has split into. This
+    /// only changes the bullet’s velocity; moving it is still up to the caller.
+    pub fn update(&mut self, delta_time: Duration, target: crate::Point) -> Vec<Self> {
+        self.age += delta_time;
+
+        let mut children = vec![];
+
+        for behaviour in &self.behaviours {
+            let child_velocities =
+                behaviour.apply(&mut self.velocity, self.pos, target, self.age, delta_time);
+
+            if let Some(child_velocities) = child_velocities {
+                self.has_split = true;
+                children.extend(child_velocities.into_iter().map(|velocity| {
+                    let mut child = Self::with_velocity(self.pos, velocity)
+                        .with_shape(Self::SPLIT_SHAPE)
+                        .with_color(self.color.into());
+                    child.owner = self.owner;
+
+                    child
+                }));
+            }
+        }
+
+        children
+    }
+
+    /// Bounces or wraps the bullet around the edges of the screen, if its edge policy says to.
+    pub fn interact_with_edges(&mut self, screen: graphics::Rect) {
+        let hitbox = self.hitbox();
+
+        match &mut self.edge_policy {
+            crate::EdgePolicy::Despawn => (),
+            crate::EdgePolicy::Bounce(bounces_left) => {
+                if *bounces_left == 0 {
+                    return;
+                }
+
+                // Only bounce bullets that are heading out of the screen, so that a bullet which
+                // is still overlapping the edge after bouncing doesn’t bounce straight back out.
+                let is_leaving_horizontally = (hitbox.x < screen.x && self.velocity.x < 0.0)
+                    || (hitbox.x + hitbox.w > screen.x + screen.w && self.velocity.x > 0.0);
+                let is_leaving_vertically = (hitbox.y < screen.y && self.velocity.y < 0.0)
+                    || (hitbox.y + hitbox.h > screen.y + screen.h && self.velocity.y > 0.0);
+
+                if is_leaving_horizontally {
+                    self.velocity.x = -self.velocity.x;
+                }
+                if is_leaving_vertically {
+                    self.velocity.y = -self.velocity.y;
+                }
+                if is_leaving_horizontally || is_leaving_vertically {
+                    *bounces_left -= 1;
+                }
+            }
+            crate::EdgePolicy::WrapHorizontally(wraps_left) => {
+                if *wraps_left == 0 {
+                    return;
+                }
+
+                // Wait until the bullet is completely off the screen before wrapping it, so that
+                // it doesn’t visibly jump.
+                if hitbox.x + hitbox.w < screen.x && self.velocity.x < 0.0 {
+                    self.pos.x += screen.w + hitbox.w;
+                    *wraps_left -= 1;
+                } else if hitbox.x > screen.x + screen.w && self.velocity.x > 0.0 {
+                    self.pos.x -= screen.w + hitbox.w;
+                    *wraps_left -= 1;
+                }
+            }
         }
     }
+
+    /// Draws the bullet tinted and turned to face the way it is flying.
+    pub fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        // Sprites point up, which is a quarter turn anticlockwise from an angle of zero.
+        let rotation = self.velocity.y.atan2(self.velocity.x) + std::f32::consts::FRAC_PI_2;
+        let image = self.sprite_cache.image(ctx)?;
+
+        graphics::draw(
+            ctx,
+            image,
+            graphics::DrawParam::new()
+                .dest(self.pos)
+                .offset(crate::Point::new(0.5, 0.5))
+                .rotation(rotation)
+                .color(self.color.into()),
+        )
+    }
+
+    /// Whether the bullet has split or outlived its lifetime, and so should be removed.
+    pub fn is_expired(&self) -> bool {
+        self.has_split
+            || self
+                .behaviours
+                .iter()
+                .any(|behaviour| behaviour.has_expired(self.age))
+    }
+
+    /// Creates a bullet heading straight for the target.
+    pub fn aimed(pos: crate::Point, target: crate::Point, speed: f32) -> Self {
+        let direction = target - pos;
+
+        // Normalising a zero vector ends up with NaN, so fire straight down if we are right on top
+        // of the target.
+        let velocity = if direction == crate::Vector::zeros() {
+            crate::Vector::new(0.0, speed)
+        } else {
+            direction.normalize() * speed
+        };
+
+        Self::with_velocity(pos, velocity)
+    }
+
+    /// Creates `count` bullets fanned out over `spread` radians, centred on the direction of the
+    /// target.
+    pub fn spread(
+        pos: crate::Point,
+        target: crate::Point,
+        count: usize,
+        spread: f32,
+        speed: f32,
+    ) -> Vec<Self> {
+        let to_target = target - pos;
+        let angle_to_target = to_target.y.atan2(to_target.x);
+
+        // A single bullet has nothing to fan out with, so it just goes straight for the target.
+        let gap = if count > 1 {
+            spread / (count - 1) as f32
+        } else {
+            0.0
+        };
+        let first_angle = angle_to_target - gap * count.saturating_sub(1) as f32 / 2.0;
+
+        (0..count)
+            .map(|i| {
+                let angle = first_angle + gap * i as f32;
+                let velocity = crate::Vector::new(angle.cos(), angle.sin()) * speed;
+
+                Self::with_velocity(pos, velocity)
+            })
+            .collect()
+    }
+
+    /// Creates `count` bullets spread evenly in a circle around `pos`, with the first one heading
+    /// in the direction of `angle` (in radians).
+    pub fn ring(pos: crate::Point, count: usize, angle: f32, speed: f32) -> Vec<Self> {
+        (0..count)
+            .map(|i| {
+                let angle = angle + i as f32 * std::f32::consts::PI * 2.0 / count as f32;
+                let velocity = crate::Vector::new(angle.cos(), angle.sin()) * speed;
+
+                Self::with_velocity(pos, velocity)
+            })
+            .collect()
+    }
 }
 
 impl crate::Position for Bullet {
@@ -56,18 +279,31 @@ impl crate::Velocity for Bullet {
 impl crate::StepDistance for Bullet {}
 
 impl crate::Damage for Bullet {
-    const DAMAGE: u16 = 1;
-}
+    fn damage(&self) -> u16 {
+        1
+    }
 
-impl Sprite for Bullet {}
+    fn damage_kind(&self) -> crate::DamageKind {
+        crate::DamageKind::Kinetic
+    }
+}
 
-impl AsRef<graphics::Image> for Bullet {
-    fn as_ref(&self) -> &graphics::Image {
+impl AsRef<crate::SpriteCache> for Bullet {
+    fn as_ref(&self) -> &crate::SpriteCache {
         &self.sprite_cache
     }
 }
 
+impl crate::RestoreSprite for Bullet {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(self.shape.img_data());
+    }
+}
+
 impl crate::Hitbox for Bullet {
-    const WIDTH_MUL: f32 = 0.85;
-    const HEIGHT_MUL: f32 = 0.85;
+    fn hitbox(&self) -> graphics::Rect {
+        let size = self.shape.hitbox_size();
+
+        graphics::Rect::new(self.pos.x - size / 2.0, self.pos.y - size / 2.0, size, size)
+    }
 }
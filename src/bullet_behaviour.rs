@@ -0,0 +1,207 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// Something a bullet does on top of flying in a straight line. Behaviours can be combined, and
+/// are applied in the order they were added to the bullet.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum BulletBehaviour {
+    /// Speeds up along the direction of travel by this much speed per second, or slows down if
+    /// negative.
+    Accelerate(f32),
+    /// Turns at a constant rate, in radians per second. Positive values turn clockwise on screen.
+    Curve(f32),
+    /// Keeps the bullet’s speed within bounds, which is mostly useful alongside acceleration.
+    SpeedLimit { min: f32, max: f32 },
+    /// Turns towards the target by no more than `turn_rate` radians per second, either for a while
+    /// or forever.
+    Home {
+        turn_rate: f32,
+        duration: Option<Duration>,
+    },
+    /// Changes direction once, after the bullet has been flying for `after`, optionally changing
+    /// speed too.
+    ChangeDirection {
+        after: Duration,
+        direction: NewDirection,
+        speed: Option<f32>,
+    },
+    /// Makes the bullet disappear once it has been flying for this long.
+    Lifetime(Duration),
+    /// Replaces the bullet with `count` small bullets of the same colour, fanned out over `spread`
+    /// radians around its direction of travel. A spread of a full turn or more makes a ring.
+    Split {
+        trigger: SplitTrigger,
+        count: usize,
+        spread: f32,
+        speed: f32,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum SplitTrigger {
+    /// Split once the bullet has been flying for this long.
+    After(Duration),
+    /// Split once the bullet gets close to this point.
+    AtPoint(crate::Point),
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum NewDirection {
+    /// Aim straight at the target.
+    AtTarget,
+    /// Turn by this many radians.
+    Turn(f32),
+    /// Head off at this angle, in radians.
+    Angle(f32),
+}
+
+/// How close a bullet has to get to the point it splits at. This is a bit bigger than the
+/// distance a fast bullet covers in a tick, so that bullets can’t skip over the point.
+const SPLIT_POINT_RADIUS: f32 = 8.0;
+
+impl BulletBehaviour {
+    /// Applies the behaviour to a bullet’s velocity for a tick that took the bullet’s age from
+    /// `age - delta_time` to `age`. If the behaviour splits the bullet, this returns the
+    /// velocities of the bullets it splits into.
+    pub fn apply(
+        &self,
+        velocity: &mut crate::Vector,
+        pos: crate::Point,
+        target: crate::Point,
+        age: Duration,
+        delta_time: Duration,
+    ) -> Option<Vec<crate::Vector>> {
+        let delta_time_secs = delta_time.as_secs_f32();
+
+        match *self {
+            BulletBehaviour::Accelerate(acceleration) => {
+                let speed = velocity.norm();
+
+                // A stationary bullet has no direction to accelerate in.
+                if speed != 0.0 {
+                    let new_speed = (speed + acceleration * delta_time_secs).max(0.0);
+                    *velocity *= new_speed / speed;
+                }
+            }
+            BulletBehaviour::Curve(turn_rate) => {
+                *velocity = rotate(*velocity, turn_rate * delta_time_secs);
+            }
+            BulletBehaviour::SpeedLimit { min, max } => {
+                let speed = velocity.norm();
+
+                if speed != 0.0 {
+                    *velocity *= num::clamp(speed, min, max) / speed;
+                }
+            }
+            BulletBehaviour::Home {
+                turn_rate,
+                duration,
+            } => {
+                let is_homing = duration.map_or(true, |duration| age <= duration);
+                let to_target = target - pos;
+
+                if is_homing && to_target != crate::Vector::zeros() {
+                    let angle_to_target = angle_between(*velocity, to_target);
+                    let max_turn = turn_rate * delta_time_secs;
+
+                    *velocity = rotate(*velocity, num::clamp(angle_to_target, -max_turn, max_turn));
+                }
+            }
+            BulletBehaviour::ChangeDirection {
+                after,
+                direction,
+                speed,
+            } => {
+                // Only change direction on the tick where the bullet reaches the right age.
+                let has_just_reached_age = age >= after && age.saturating_sub(delta_time) < after;
+
+                if has_just_reached_age {
+                    let speed = speed.unwrap_or_else(|| velocity.norm());
+                    let to_target = target - pos;
+
+                    let angle = match direction {
+                        NewDirection::AtTarget => to_target.y.atan2(to_target.x),
+                        NewDirection::Turn(turn) => velocity.y.atan2(velocity.x) + turn,
+                        NewDirection::Angle(angle) => angle,
+                    };
+
+                    *velocity = crate::Vector::new(angle.cos(), angle.sin()) * speed;
+                }
+            }
+            BulletBehaviour::Lifetime(_) => (),
+            BulletBehaviour::Split {
+                trigger,
+                count,
+                spread,
+                speed,
+            } => {
+                let should_split = match trigger {
+                    SplitTrigger::After(after) => {
+                        age >= after && age.saturating_sub(delta_time) < after
+                    }
+                    SplitTrigger::AtPoint(point) => (point - pos).norm() <= SPLIT_POINT_RADIUS,
+                };
+
+                if should_split {
+                    let direction = velocity.y.atan2(velocity.x);
+
+                    return Some(
+                        fan_angles(direction, count, spread)
+                            .map(|angle| crate::Vector::new(angle.cos(), angle.sin()) * speed)
+                            .collect(),
+                    );
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Whether the behaviour means a bullet of this age should be removed.
+    pub fn has_expired(&self, age: Duration) -> bool {
+        match *self {
+            BulletBehaviour::Lifetime(lifetime) => age >= lifetime,
+            _ => false,
+        }
+    }
+}
+
+/// Angles for `count` directions fanned out evenly over `spread` radians, centred on `direction`.
+fn fan_angles(direction: f32, count: usize, spread: f32) -> impl Iterator<Item = f32> {
+    use std::f32::consts::PI;
+
+    // In a full ring the first and last bullets would overlap, so the gap is worked out as if there
+    // were one more bullet.
+    let gap = if spread >= 2.0 * PI {
+        2.0 * PI / count as f32
+    } else if count > 1 {
+        spread / (count - 1) as f32
+    } else {
+        0.0
+    };
+    let first_angle = direction - gap * count.saturating_sub(1) as f32 / 2.0;
+
+    (0..count).map(move |i| first_angle + gap * i as f32)
+}
+
+fn rotate(v: crate::Vector, angle: f32) -> crate::Vector {
+    let (sin, cos) = angle.sin_cos();
+    crate::Vector::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+/// The signed angle to turn `from` by to face the same way as `to`, between -π and π.
+fn angle_between(from: crate::Vector, to: crate::Vector) -> f32 {
+    use std::f32::consts::PI;
+
+    let angle = to.y.atan2(to.x) - from.y.atan2(from.x);
+
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
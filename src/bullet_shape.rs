@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// The look of a bullet. Bullet sprites are white so that they can be tinted any colour, and
+/// point up so that they can be rotated to face the way they are flying.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum BulletShape {
+    SmallRound,
+    /// A thin grain, which is fairly harmless for its size.
+    Rice,
+    /// A dagger, with a hitbox much smaller than it looks.
+    Kunai,
+    #[default]
+    LargeOrb,
+    Star,
+}
+
+impl BulletShape {
+    pub fn img_data(self) -> &'static [u8] {
+        match self {
+            BulletShape::SmallRound => include_bytes!("bullet_small_round.png"),
+            BulletShape::Rice => include_bytes!("bullet_rice.png"),
+            BulletShape::Kunai => include_bytes!("bullet_kunai.png"),
+            BulletShape::LargeOrb => include_bytes!("bullet_large_orb.png"),
+            BulletShape::Star => include_bytes!("bullet_star.png"),
+        }
+    }
+
+    /// The width and height of the square hitbox around the bullet’s centre. These are kept a
+    /// bit smaller than the sprites, and square so that they don’t change as the bullet turns.
+    pub fn hitbox_size(self) -> f32 {
+        match self {
+            BulletShape::SmallRound => 6.0,
+            BulletShape::Rice => 4.0,
+            BulletShape::Kunai => 4.0,
+            BulletShape::LargeOrb => 13.0,
+            BulletShape::Star => 8.0,
+        }
+    }
+}
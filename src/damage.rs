@@ -1,13 +1,54 @@
+/// What kind of harm something does, so that targets can be tougher against some kinds than others.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    /// Solid projectiles, like bullets and needles.
+    Kinetic,
+    /// Lasers and beams.
+    Energy,
+}
+
 pub trait Damage {
-    const DAMAGE: u16;
+    /// How much damage is done before the target’s resistances are taken into account.
+    fn damage(&self) -> u16;
+    fn damage_kind(&self) -> DamageKind;
+}
+
+/// What happened when something took damage.
+#[derive(Clone, Copy, Default)]
+pub struct DamageOutcome {
+    /// How much health was actually lost.
+    pub dealt: u16,
+    /// Whether this was the fatal blow.
+    pub killed: bool,
+    /// How much of the damage was shrugged off by the target’s resistances.
+    pub blocked: u16,
 }
 
 pub trait TakeDamage: crate::Health {
-    fn take_damage<D: Damage>(&mut self, _damager: &D) {
+    /// How much of each kind of damage gets through. Anything below 1 is armour, anything above is
+    /// a weakness.
+    fn damage_multiplier(&self, _kind: DamageKind) -> f32 {
+        1.0
+    }
+
+    fn take_damage<D: Damage + ?Sized>(&mut self, damager: &D) -> DamageOutcome {
+        let was_dead = self.is_dead();
+        let damage = damager.damage();
+
+        // Round up, so that armour only softens hits rather than stopping weak ones outright. Only
+        // a target that is immune to the damage takes none of it.
+        let multiplier = self.damage_multiplier(damager.damage_kind());
+        let taken = (f32::from(damage) * multiplier).ceil() as u16;
+
         // We subtract without underflowing to keep the health at zero if a fatal shot has been
         // fired.
-        *self.health_mut() = self.health().saturating_sub(D::DAMAGE);
+        let health = self.health();
+        *self.health_mut() = health.saturating_sub(taken);
+
+        DamageOutcome {
+            dealt: health - self.health(),
+            killed: !was_dead && self.is_dead(),
+            blocked: damage.saturating_sub(taken),
+        }
     }
 }
-
-impl<T: crate::Health> TakeDamage for T {}
@@ -0,0 +1,128 @@
+use {ggez::graphics, std::time::Duration};
+
+const HITBOX_COLOR: (u8, u8, u8) = (229, 37, 72);
+const HITBOX_LINE_WIDTH: f32 = 1.0;
+const TEXT_COLOR: (u8, u8, u8) = (0, 0, 0);
+const TEXT_MARGIN: f32 = 8.0;
+const FONT_SIZE: f32 = 20.0;
+
+/// Figures about the running simulation that are shown on the debug overlay.
+pub struct DebugStats {
+    pub bullets: usize,
+    pub lasers: usize,
+    pub enemies: usize,
+    pub seed: u64,
+    pub boss_phase: Option<u16>,
+    pub rank: f32,
+}
+
+/// A toggleable overlay that outlines hitboxes and shows simulation statistics. While it is
+/// visible the simulation can also be paused and stepped forward one tick at a time.
+#[derive(Default)]
+pub struct DebugOverlay {
+    visible: bool,
+    paused: bool,
+    step_requested: bool,
+    tick_time: Duration,
+}
+
+impl DebugOverlay {
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle_visible(&mut self) {
+        self.visible = !self.visible;
+
+        // Hiding the overlay hides the pause indicator too, so we resume the simulation rather
+        // than leaving the game silently stuck.
+        if !self.visible {
+            self.paused = false;
+        }
+    }
+
+    pub fn toggle_paused(&mut self) {
+        if self.visible {
+            self.paused = !self.paused;
+        }
+    }
+
+    pub fn request_step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+
+    /// Returns whether the simulation should advance by a tick, consuming any pending frame-step.
+    pub fn should_tick(&mut self) -> bool {
+        if !self.paused {
+            return true;
+        }
+
+        std::mem::replace(&mut self.step_requested, false)
+    }
+
+    pub fn record_tick_time(&mut self, tick_time: Duration) {
+        self.tick_time = tick_time;
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut ggez::Context,
+        hitboxes: &[graphics::Rect],
+        stats: &DebugStats,
+    ) -> ggez::GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        // Building a mesh without any geometry is an error, so only outline hitboxes if there are
+        // some.
+        if !hitboxes.is_empty() {
+            let mut builder = graphics::MeshBuilder::new();
+
+            for hitbox in hitboxes {
+                builder.rectangle(
+                    graphics::DrawMode::stroke(HITBOX_LINE_WIDTH),
+                    *hitbox,
+                    HITBOX_COLOR.into(),
+                );
+            }
+
+            let mesh = builder.build(ctx)?;
+            graphics::draw(ctx, &mesh, (crate::Point::new(0.0, 0.0),))?;
+        }
+
+        let text = {
+            let boss_phase = match stats.boss_phase {
+                Some(phase) => phase.to_string(),
+                None => "-".to_string(),
+            };
+
+            let text = format!(
+                "FPS: {:.0}\nTick: {:.2} ms\nBullets: {}\nLasers: {}\nEnemies: {}\nSeed: {}\nBoss phase: {}\nRank: {:.2}{}",
+                ggez::timer::fps(ctx),
+                self.tick_time.as_secs_f64() * 1000.0,
+                stats.bullets,
+                stats.lasers,
+                stats.enemies,
+                stats.seed,
+                boss_phase,
+                stats.rank,
+                if self.paused {
+                    "\nPaused (F5 to step)"
+                } else {
+                    ""
+                },
+            );
+
+            graphics::Text::new(
+                graphics::TextFragment::new(text)
+                    .scale(graphics::Scale::uniform(FONT_SIZE))
+                    .color(TEXT_COLOR.into()),
+            )
+        };
+
+        graphics::draw(ctx, &text, (crate::Point::new(TEXT_MARGIN, TEXT_MARGIN),))
+    }
+}
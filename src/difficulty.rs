@@ -0,0 +1,88 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::collections::HashMap,
+};
+
+const DIFFICULTY_DATA: &str = include_str!("difficulties.ron");
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Lunatic,
+}
+
+impl Difficulty {
+    /// Every difficulty, from easiest to hardest.
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Lunatic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Lunatic => "Lunatic",
+        }
+    }
+
+    /// The next harder difficulty, or this one if it is already the hardest.
+    pub fn harder(self) -> Self {
+        let index = Self::ALL.iter().position(|&d| d == self).unwrap();
+
+        Self::ALL[(index + 1).min(Self::ALL.len() - 1)]
+    }
+
+    /// The next easier difficulty, or this one if it is already the easiest.
+    pub fn easier(self) -> Self {
+        let index = Self::ALL.iter().position(|&d| d == self).unwrap();
+
+        Self::ALL[index.saturating_sub(1)]
+    }
+
+    /// Looks up how this difficulty plays in the difficulty data file.
+    pub fn settings(self) -> anyhow::Result<DifficultySettings> {
+        let mut all_settings: HashMap<Difficulty, DifficultySettings> =
+            ron::de::from_str(DIFFICULTY_DATA)?;
+
+        all_settings
+            .remove(&self)
+            .ok_or_else(|| anyhow::anyhow!("no settings for the {} difficulty", self.name()))
+    }
+}
+
+/// How a difficulty plays, as written in the difficulty data file.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub struct DifficultySettings {
+    /// How fast enemy bullets travel at a speed of 1, in pixels per second.
+    pub bullet_speed: f32,
+    /// How many bullets enemies fire in each volley, relative to Normal.
+    pub bullet_density: f32,
+    /// How much health enemies have, relative to Normal.
+    pub enemy_toughness: f32,
+    pub starting_lives: u8,
+    pub starting_bombs: u8,
+    pub score_multiplier: f32,
+}
+
+impl DifficultySettings {
+    /// Scales the number of bullets in a volley, always leaving at least one.
+    pub fn bullet_count(&self, count: usize) -> usize {
+        ((count as f32 * self.bullet_density).round() as usize).max(1)
+    }
+
+    /// Scales an enemy’s health, always leaving it with at least one point.
+    pub fn max_health(&self, health: u16) -> u16 {
+        ((f32::from(health) * self.enemy_toughness).round() as u16).max(1)
+    }
+
+    pub fn score(&self, score: u32) -> u32 {
+        (score as f32 * self.score_multiplier).round() as u32
+    }
+}
@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// What a bullet does when it reaches the edge of the screen.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub enum EdgePolicy {
+    /// Fly off the screen and be removed.
+    #[default]
+    Despawn,
+    /// Bounce off the edges of the screen this many times before flying off.
+    Bounce(u32),
+    /// Reappear on the other side after leaving the left or right of the screen this many times.
+    /// Bullets still fly off the top and bottom.
+    WrapHorizontally(u32),
+}
@@ -0,0 +1,263 @@
+use {
+    crate::{Health, HealthBar, Hitbox, ImageDrawable, RestoreSprite, TakeDamage},
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// Identifies an enemy in play, so that things it leaves behind (like its bullets) can be traced
+/// back to it.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnemyId(pub u32);
+
+/// The scripted parts of an opponent: how it moves, how it attacks and what happens when it is
+/// destroyed. Anything with this behaviour, the ability to take damage and a hitbox is
+/// automatically an [`Enemy`].
+pub trait EnemyBehaviour {
+    /// How many points the player gets for destroying the enemy.
+    const SCORE_VALUE: u32;
+
+    /// Only sturdier enemies are worth cluttering the screen with a health bar.
+    const SHOWS_HEALTH_BAR: bool = false;
+
+    /// The chance of the enemy dropping a point item each time a laser hits it.
+    const HIT_DROP_CHANCE: f64 = 0.0;
+
+    fn movement(&mut self, screen: graphics::Rect, delta_time: Duration);
+
+    fn attack(
+        &mut self,
+        screen: graphics::Rect,
+        rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet>;
+
+    /// Bullets released when the enemy is destroyed.
+    fn death_behaviour(
+        &self,
+        _rng: &mut crate::GameRng,
+        _target: crate::Point,
+        _difficulty: &crate::DifficultySettings,
+    ) -> Vec<crate::Bullet> {
+        vec![]
+    }
+
+    /// Items dropped when the enemy is destroyed.
+    fn drops(&self, _rng: &mut crate::GameRng) -> Vec<crate::ItemKind> {
+        vec![]
+    }
+
+    /// Warnings for attacks the enemy has lined up since this was last asked. This is asked once
+    /// a tick, after the enemy has attacked.
+    fn telegraphs(&mut self) -> Vec<crate::Telegraph> {
+        vec![]
+    }
+
+    /// Beams the enemy is firing. Enemies keep hold of their beams so that the beams can move
+    /// along with them.
+    fn beams(&self) -> &[crate::Beam] {
+        &[]
+    }
+
+    /// Whether the enemy has flown away and can be forgotten about without being destroyed.
+    fn has_left_screen(&self, _screen: graphics::Rect) -> bool {
+        false
+    }
+
+    /// Only bosses have phases.
+    fn boss_phase(&self) -> Option<u16> {
+        None
+    }
+
+    /// The named attack the boss is using in its current phase.
+    fn boss_attack(&self) -> Option<crate::BossAttack> {
+        None
+    }
+
+    /// Skips the rest of the current phase, because its time limit has run out. Running out of
+    /// time on the last phase makes the boss retreat rather than be destroyed, since it hasn’t
+    /// been beaten, so it should stop being a boss and leave the screen.
+    fn end_boss_phase(&mut self) {}
+}
+
+/// An opponent of any kind, so that different kinds of enemy can be stored together. Enemies can
+/// be cloned and saved, so that the whole game can be copied for rolling back to or saved to a
+/// file.
+pub trait Enemy {
+    /// Moves the enemy and returns any bullets it fires.
+    fn update(
+        &mut self,
+        screen: graphics::Rect,
+        rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet>;
+
+    fn telegraphs(&mut self) -> Vec<crate::Telegraph>;
+    fn beams(&self) -> &[crate::Beam];
+    fn take_damage(&mut self, damager: &dyn crate::Damage) -> crate::DamageOutcome;
+    fn hitbox(&self) -> graphics::Rect;
+    fn is_dead(&self) -> bool;
+    fn has_left_screen(&self, screen: graphics::Rect) -> bool;
+    fn score_value(&self) -> u32;
+
+    fn die(
+        &self,
+        rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+    ) -> Vec<crate::Bullet>;
+
+    fn drops(&self, rng: &mut crate::GameRng) -> Vec<crate::ItemKind>;
+    fn hit_drop_chance(&self) -> f64;
+    fn boss_phase(&self) -> Option<u16>;
+    fn boss_attack(&self) -> Option<crate::BossAttack>;
+    fn end_boss_phase(&mut self);
+
+    fn is_boss(&self) -> bool {
+        self.boss_phase().is_some()
+    }
+
+    fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult;
+
+    fn clone_box(&self) -> Box<dyn Enemy>;
+    fn save(&self) -> SavedEnemy;
+    fn restore_sprite(&mut self);
+}
+
+impl Clone for Box<dyn Enemy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl Serialize for Box<dyn Enemy> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.save().serialize(serializer)
+    }
+}
+
+/// Loaded enemies have no sprites until they are restored.
+impl<'de> Deserialize<'de> for Box<dyn Enemy> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match SavedEnemy::deserialize(deserializer)? {
+            SavedEnemy::PopcornFighter(enemy) => Box::new(enemy),
+            SavedEnemy::Turret(enemy) => Box::new(enemy),
+            SavedEnemy::Gunship(enemy) => Box::new(enemy),
+            SavedEnemy::SkyCore(enemy) => Box::new(enemy),
+        })
+    }
+}
+
+/// An enemy of any kind, in a form that can be saved, since enemies are otherwise only known by
+/// what they can do.
+#[derive(Serialize, Deserialize)]
+pub enum SavedEnemy {
+    PopcornFighter(crate::PopcornFighter),
+    Turret(crate::Turret),
+    Gunship(crate::Gunship),
+    SkyCore(crate::SkyCore),
+}
+
+impl<T> Enemy for T
+where
+    T: EnemyBehaviour + TakeDamage + Hitbox + RestoreSprite + Clone + Into<SavedEnemy> + 'static,
+{
+    fn update(
+        &mut self,
+        screen: graphics::Rect,
+        rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet> {
+        self.movement(screen, delta_time);
+        self.attack(screen, rng, target, difficulty, delta_time)
+    }
+
+    fn telegraphs(&mut self) -> Vec<crate::Telegraph> {
+        EnemyBehaviour::telegraphs(self)
+    }
+
+    fn beams(&self) -> &[crate::Beam] {
+        EnemyBehaviour::beams(self)
+    }
+
+    fn take_damage(&mut self, damager: &dyn crate::Damage) -> crate::DamageOutcome {
+        TakeDamage::take_damage(self, damager)
+    }
+
+    fn hitbox(&self) -> graphics::Rect {
+        Hitbox::hitbox(self)
+    }
+
+    fn is_dead(&self) -> bool {
+        Health::is_dead(self)
+    }
+
+    fn has_left_screen(&self, screen: graphics::Rect) -> bool {
+        EnemyBehaviour::has_left_screen(self, screen)
+    }
+
+    fn score_value(&self) -> u32 {
+        T::SCORE_VALUE
+    }
+
+    fn die(
+        &self,
+        rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+    ) -> Vec<crate::Bullet> {
+        self.death_behaviour(rng, target, difficulty)
+    }
+
+    fn drops(&self, rng: &mut crate::GameRng) -> Vec<crate::ItemKind> {
+        EnemyBehaviour::drops(self, rng)
+    }
+
+    fn hit_drop_chance(&self) -> f64 {
+        T::HIT_DROP_CHANCE
+    }
+
+    fn boss_phase(&self) -> Option<u16> {
+        EnemyBehaviour::boss_phase(self)
+    }
+
+    fn boss_attack(&self) -> Option<crate::BossAttack> {
+        EnemyBehaviour::boss_attack(self)
+    }
+
+    fn end_boss_phase(&mut self) {
+        EnemyBehaviour::end_boss_phase(self)
+    }
+
+    fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        for beam in EnemyBehaviour::beams(self) {
+            beam.draw(ctx)?;
+        }
+
+        ImageDrawable::draw(self, ctx)?;
+
+        if T::SHOWS_HEALTH_BAR {
+            self.health_bar(ctx)?.draw(ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn Enemy> {
+        Box::new(self.clone())
+    }
+
+    fn save(&self) -> SavedEnemy {
+        self.clone().into()
+    }
+
+    fn restore_sprite(&mut self) {
+        RestoreSprite::restore_sprite(self)
+    }
+}
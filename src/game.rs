@@ -0,0 +1,1192 @@
+use {
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::{path::Path, time::Duration},
+};
+
+/// How many players a run can have at most.
+pub const MAX_PLAYERS: usize = 2;
+/// The area the game is played in, which is the size of the default window. Runs are always played
+/// at this size, whatever the size of the window or whether there is one at all, so that a run
+/// plays out the same way in replays, online games and the headless simulator.
+pub const PLAYFIELD: graphics::Rect = graphics::Rect {
+    x: 0.0,
+    y: 0.0,
+    w: 800.0,
+    h: 600.0,
+};
+const SHIP_SPEED: f32 = 200.0;
+/// How fast the ship moves while focusing, for weaving through tight gaps.
+const SHIP_FOCUS_SPEED: f32 = 90.0;
+const LASER_SPEED: f32 = 900.0;
+const SHIP_OFFSET_FROM_BOTTOM: f32 = 100.0;
+/// Points for getting through a boss attack without being hit or using a bomb.
+const CAPTURE_BONUS: u32 = 5000;
+/// How close a bullet has to pass by the middle of the ship to graze it.
+const GRAZE_RADIUS: f32 = 24.0;
+const GRAZE_POINTS: u32 = 50;
+/// Points for every second survived in survival mode.
+const SURVIVAL_POINTS_PER_SECOND: u32 = 100;
+/// The lives and bombs a boss rush starts with, which have to last the whole run.
+const BOSS_RUSH_LIVES: u8 = 2;
+const BOSS_RUSH_BOMBS: u8 = 3;
+/// How far up the screen the ship has to go, as a fraction of the screen’s height, for every item
+/// on the screen to fly to it.
+const POINT_OF_COLLECTION: f32 = 0.25;
+/// How close items have to be to a focusing ship to fly to it.
+const FOCUS_MAGNET_RADIUS: f32 = 96.0;
+/// How far from the middle of a destroyed enemy its items are scattered.
+const ITEM_SCATTER: f32 = 16.0;
+const STAGE_TRANSITION_DURATION: Duration = Duration::from_secs(3);
+pub const TICKS_PER_SECOND: u32 = 60;
+pub const TICK_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND as u64);
+
+/// An enemy in play, tagged so that the bullets it fires can be traced back to it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrackedEnemy {
+    id: crate::EnemyId,
+    enemy: Box<dyn crate::Enemy>,
+    /// The boss phase the enemy was in as of the last tick, for noticing when a phase is cleared.
+    boss_phase: Option<u16>,
+    /// The boss attack the player is facing, if the enemy is a boss.
+    attempt: Option<crate::AttackAttempt>,
+    /// The player who last hit the enemy, who gets the points for destroying it.
+    last_hit_by: Option<usize>,
+}
+
+impl TrackedEnemy {
+    fn new(id: crate::EnemyId, enemy: Box<dyn crate::Enemy>) -> Self {
+        Self {
+            id,
+            boss_phase: enemy.boss_phase(),
+            attempt: enemy.boss_attack().map(crate::AttackAttempt::new),
+            last_hit_by: None,
+            enemy,
+        }
+    }
+
+    /// The boss attack the player is facing, if the enemy is a boss.
+    pub fn attempt(&self) -> Option<&crate::AttackAttempt> {
+        self.attempt.as_ref()
+    }
+}
+
+impl std::ops::Deref for TrackedEnemy {
+    type Target = dyn crate::Enemy;
+
+    fn deref(&self) -> &Self::Target {
+        self.enemy.as_ref()
+    }
+}
+
+impl std::ops::DerefMut for TrackedEnemy {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.enemy.as_mut()
+    }
+}
+
+/// One of the players, with their own ship, lasers and score.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Player {
+    ship: crate::Ship,
+    lasers: Vec<crate::Laser>,
+    time_since_shot_laser: Duration,
+    /// Whether the player was focusing as of the last tick.
+    is_focused: bool,
+    score: u32,
+    grazes: u32,
+}
+
+impl Player {
+    fn new(ship: crate::Ship) -> Self {
+        Self {
+            // If the time since the laser was last shot is the laser cooldown, then this means
+            // that we can start shooting immediately
+            time_since_shot_laser: ship.shot_cooldown(),
+            ship,
+            lasers: vec![],
+            is_focused: false,
+            score: 0,
+            grazes: 0,
+        }
+    }
+
+    /// Whether the player is still in the game, rather than having run out of lives.
+    pub fn is_playing(&self) -> bool {
+        use crate::Health;
+
+        !self.ship.is_dead()
+    }
+
+    pub fn ship(&self) -> &crate::Ship {
+        &self.ship
+    }
+
+    pub fn lasers(&self) -> &[crate::Laser] {
+        &self.lasers
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn grazes(&self) -> u32 {
+        self.grazes
+    }
+}
+
+/// Everything that is simulated in a run, kept apart from how the run is shown and controlled. A
+/// game only moves on by being given every player’s input for a tick, so the same seed, setup
+/// and inputs always play out the same way. Copies of a game can be kept to go back to, or saved
+/// to a file to be carried on with later.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Game {
+    players: Vec<Player>,
+    enemies: Vec<TrackedEnemy>,
+    next_enemy_id: u32,
+    bullets: Vec<crate::Bullet>,
+    telegraphs: Vec<crate::Telegraph>,
+    items: Vec<crate::Item>,
+    stage_index: usize,
+    stage: crate::StageRunner,
+    /// How long is left of the break between the stage that was just cleared and the next one.
+    stage_transition: Option<Duration>,
+    /// How many ticks have been played.
+    tick: u32,
+    setup: RunSetup,
+    difficulty_settings: crate::DifficultySettings,
+    rank: crate::Rank,
+    seed: u64,
+    rng: crate::GameRng,
+    /// How many of the next boss’s phases to skip, when practising one of its later phases.
+    boss_phases_to_skip: u16,
+    /// Sends in the waves instead of the stages, in survival mode.
+    survival: Option<crate::SurvivalDirector>,
+    /// Sends in the bosses instead of the stages, in boss rush mode.
+    boss_rush: Option<crate::BossRush>,
+    /// How the run ended, once it has.
+    outcome: Option<Outcome>,
+    stats: RunStats,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Died,
+    Won,
+}
+
+/// Where the bullets and beams in play are expected to be on each of the next few ticks.
+#[derive(Default)]
+pub struct Forecast {
+    /// The hitboxes of the bullets on each tick.
+    pub bullets: Vec<Vec<graphics::Rect>>,
+    pub beams: Vec<Vec<crate::Beam>>,
+}
+
+/// Figures about how a run went, for balancing the game.
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+pub struct RunStats {
+    /// How much health the players lost between them, before any of it was made up for by
+    /// respawning.
+    pub damage_taken: u32,
+    /// How many bullets the enemies fired, including bullets that split off from other bullets.
+    pub bullets_spawned: u32,
+    /// How many boss attacks ran out of time before the players got through them.
+    pub attacks_timed_out: u32,
+}
+
+/// How a run is set up: the difficulty it is played on, what kind of run it is and how many are
+/// playing.
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+pub struct RunSetup {
+    pub difficulty: crate::Difficulty,
+    pub mode: RunMode,
+    pub is_co_op: bool,
+}
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
+pub enum RunMode {
+    #[default]
+    FullGame,
+    /// A run of part of the game, starting wherever and with whatever the player chose.
+    Practice(Practice),
+    /// Endless waves that keep getting harder until the player dies.
+    Survival,
+    /// Every boss back to back, with a fixed number of lives and bombs to get through them with.
+    BossRush,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Practice {
+    pub start: crate::PracticeStart,
+    pub lives: u8,
+    pub bombs: u8,
+    pub power_level: u8,
+}
+
+impl Game {
+    /// Starts a run on a screen of the given size.
+    pub fn new(seed: u64, setup: RunSetup, screen: graphics::Rect) -> Self {
+        use rand::SeedableRng;
+
+        // The difficulties are baked into the executable too, so failing to load them is a bug.
+        let difficulty_settings = setup
+            .difficulty
+            .settings()
+            .expect("invalid difficulty data");
+
+        // Practice runs start partway through the game.
+        let start = match setup.mode {
+            RunMode::Practice(practice) => Some(practice.start),
+            _ => None,
+        };
+        let stage_index = start.map_or(0, crate::PracticeStart::stage_index);
+        let mut stage = crate::StageRunner::new(stage_index);
+        let mut boss_phases_to_skip = 0;
+
+        if let Some(crate::PracticeStart::BossPhase { boss, phase, .. }) = start {
+            stage.skip_to_boss(boss);
+            boss_phases_to_skip = phase - 1;
+        }
+
+        // Players start spread out evenly along the bottom of the screen.
+        let player_count = if setup.is_co_op { MAX_PLAYERS } else { 1 };
+        let players = (0..player_count)
+            .map(|i| {
+                let ship = crate::Ship::new(
+                    crate::Point::new(
+                        screen.w * (i + 1) as f32 / (player_count + 1) as f32,
+                        screen.h - SHIP_OFFSET_FROM_BOTTOM,
+                    ),
+                    &difficulty_settings,
+                );
+                let ship = match setup.mode {
+                    RunMode::Practice(practice) => {
+                        ship.with_loadout(practice.lives, practice.bombs, practice.power_level)
+                    }
+                    RunMode::BossRush => ship.with_loadout(
+                        BOSS_RUSH_LIVES,
+                        BOSS_RUSH_BOMBS,
+                        crate::ShotType::MAX_POWER_LEVEL,
+                    ),
+                    _ => ship,
+                };
+
+                Player::new(ship)
+            })
+            .collect();
+
+        let boss_rush = match setup.mode {
+            RunMode::BossRush => Some(crate::BossRush::new(crate::Stage::all())),
+            _ => None,
+        };
+
+        Self {
+            players,
+            enemies: vec![],
+            next_enemy_id: 0,
+            bullets: vec![],
+            telegraphs: vec![],
+            items: vec![],
+            stage_index,
+            stage,
+            stage_transition: None,
+            tick: 0,
+            setup,
+            difficulty_settings,
+            rank: Default::default(),
+            seed,
+            rng: crate::GameRng::seed_from_u64(seed),
+            boss_phases_to_skip,
+            survival: match setup.mode {
+                RunMode::Survival => Some(Default::default()),
+                _ => None,
+            },
+            boss_rush,
+            outcome: None,
+            stats: Default::default(),
+        }
+    }
+
+    /// Saves the whole game, down to every bullet and the state of the random number generator, so
+    /// that it carries on exactly the same way once loaded.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let game = ron::ser::to_string_pretty(self, Default::default())?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(path, game)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut game: Self = ron::de::from_str(&std::fs::read_to_string(path)?)?;
+        game.restore_sprites();
+
+        Ok(game)
+    }
+
+    /// Sprites aren’t saved, so they have to be made again for everything in a loaded game.
+    fn restore_sprites(&mut self) {
+        use crate::RestoreSprite;
+
+        for player in &mut self.players {
+            player.ship.restore_sprite();
+
+            for laser in &mut player.lasers {
+                laser.restore_sprite();
+            }
+        }
+
+        for enemy in &mut self.enemies {
+            enemy.enemy.restore_sprite();
+        }
+
+        for bullet in &mut self.bullets {
+            bullet.restore_sprite();
+        }
+
+        for item in &mut self.items {
+            item.restore_sprite();
+        }
+    }
+
+    /// Plays a tick, given what each player is doing, and returns the boss attacks that finished
+    /// during it.
+    pub fn update(
+        &mut self,
+        inputs: &[crate::PlayerInput],
+        screen: graphics::Rect,
+    ) -> Vec<crate::AttackAttempt> {
+        use crate::{Health, Hitbox, OnScreen, Position, StepDistance, TakeDamage};
+
+        // Nothing happens once the run is over.
+        if self.outcome.is_some() {
+            return vec![];
+        }
+
+        self.tick += 1;
+
+        // The simulation advances in fixed-length ticks regardless of FPS, so that a run can be
+        // reproduced from its seed and stepped through one tick at a time.
+        let delta_time = TICK_DURATION;
+        let delta_time_secs = delta_time.as_secs_f32();
+
+        // Rank creeps up for as long as the players survive, and decides how hard attacks are
+        // this tick.
+        let power_level = self
+            .players
+            .iter()
+            .filter(|player| player.is_playing())
+            .map(|player| player.ship.power_level())
+            .max()
+            .unwrap_or(1);
+        self.rank.update(delta_time, power_level);
+        let pattern_settings = self.pattern_settings();
+
+        let adjusted_laser_speed = LASER_SPEED * delta_time_secs;
+        let adjusted_bullet_speed = pattern_settings.bullet_speed * delta_time_secs;
+
+        //
+        // Ships
+        //
+
+        let playing = |(player, _): &(&mut Player, &crate::PlayerInput)| player.is_playing();
+
+        // Each player switches shot types and uses bombs, which clear the screen of bullets.
+        let mut has_used_bomb = false;
+
+        for (player, input) in self.players.iter_mut().zip(inputs).filter(playing) {
+            if input.switch_shot {
+                player.ship.switch_shot_type();
+            }
+
+            if input.bomb && player.ship.use_bomb() {
+                self.bullets.clear();
+                self.rank.use_bomb();
+                has_used_bomb = true;
+            }
+        }
+
+        if has_used_bomb {
+            self.spoil_attack_attempts();
+        }
+
+        // Move each ship the way its player is steering, slowing down to focus while they are
+        // focusing.
+        for (player, input) in self.players.iter_mut().zip(inputs).filter(playing) {
+            player.is_focused = input.focus;
+            steer(&mut player.ship, input, screen);
+
+            //
+            // Lasers
+            //
+
+            // Fire lasers if the cooldown has finished.
+            if input.shoot && player.time_since_shot_laser >= player.ship.shot_cooldown() {
+                player.lasers.extend(player.ship.shoot());
+                player.time_since_shot_laser = Duration::from_millis(0);
+            } else {
+                player.time_since_shot_laser += delta_time;
+            }
+        }
+
+        // Make lasers move up the screen, with homing lasers turning towards the nearest enemy.
+        for laser in self
+            .players
+            .iter_mut()
+            .flat_map(|player| player.lasers.iter_mut())
+        {
+            let laser_pos = laser.pos();
+            let nearest_enemy = self
+                .enemies
+                .iter()
+                .map(|enemy| hitbox_center(enemy.hitbox()))
+                .min_by(|a, b| {
+                    let distance_to_a = (a - laser_pos).norm();
+                    let distance_to_b = (b - laser_pos).norm();
+
+                    distance_to_a.total_cmp(&distance_to_b)
+                });
+
+            laser.update(delta_time, nearest_enemy);
+            laser.step_distance(adjusted_laser_speed);
+        }
+
+        // Let enemies take damage for every laser that hits them. Most lasers are used up by the
+        // first enemy they hit, but piercing lasers carry on, hurting every enemy they are inside.
+        // Some enemies shed point items as they are hit, as long as the hit gets through their
+        // armour. The enemies that are finished off are noted down to be scored further on.
+        let enemies = &mut self.enemies;
+        let rng = &mut self.rng;
+        let mut hit_drops = vec![];
+        let mut killed_enemies = vec![];
+
+        for (player_index, player) in self.players.iter_mut().enumerate() {
+            player.lasers.retain(|laser| {
+                let mut hit = |enemy: &mut TrackedEnemy| {
+                    use rand::Rng;
+
+                    let outcome = enemy.take_damage(laser);
+                    enemy.last_hit_by = Some(player_index);
+
+                    if outcome.dealt > 0 && rng.gen_bool(enemy.hit_drop_chance()) {
+                        hit_drops.push(laser.pos());
+                    }
+
+                    if outcome.killed {
+                        killed_enemies.push(enemy.id);
+                    }
+                };
+
+                if laser.pierces() {
+                    for enemy in enemies
+                        .iter_mut()
+                        .filter(|enemy| laser.hitbox().overlaps(&enemy.hitbox()))
+                    {
+                        hit(enemy);
+                    }
+
+                    return true;
+                }
+
+                let hit_enemy = enemies
+                    .iter_mut()
+                    .find(|enemy| laser.hitbox().overlaps(&enemy.hitbox()));
+
+                match hit_enemy {
+                    Some(enemy) => {
+                        hit(enemy);
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        for pos in hit_drops {
+            self.items
+                .push(crate::Item::new(crate::ItemKind::Point, pos));
+        }
+
+        //
+        // Telegraphs
+        //
+
+        for telegraph in &mut self.telegraphs {
+            telegraph.update(delta_time);
+        }
+
+        self.telegraphs.retain(|telegraph| !telegraph.is_finished());
+
+        //
+        // Stage
+        //
+
+        let is_boss_alive = self.enemies.iter().any(|enemy| enemy.is_boss());
+
+        // Survival sends in its own waves instead of playing through the stages, scoring every
+        // player still in the game for every second survived.
+        let events = match &mut self.survival {
+            Some(survival) => {
+                let seconds_before = survival.time().as_secs();
+                let events = survival.update(delta_time, &mut self.rng);
+                let seconds = (survival.time().as_secs() - seconds_before) as u32;
+                let points = self
+                    .difficulty_settings
+                    .score(seconds * SURVIVAL_POINTS_PER_SECOND);
+
+                for player in self.players.iter_mut().filter(|player| player.is_playing()) {
+                    player.score += points;
+                }
+
+                events
+            }
+            None => match &mut self.boss_rush {
+                Some(boss_rush) => boss_rush.update(delta_time, is_boss_alive),
+                None => self.stage.update(delta_time, is_boss_alive),
+            },
+        };
+
+        for event in events {
+            for mut enemy in event.spawn_enemies(&self.difficulty_settings, screen) {
+                // Practising a later phase of a boss skips straight to it.
+                if enemy.is_boss() {
+                    for _ in 0..std::mem::take(&mut self.boss_phases_to_skip) {
+                        enemy.end_boss_phase();
+                    }
+                }
+
+                let id = crate::EnemyId(self.next_enemy_id);
+                self.next_enemy_id += 1;
+
+                self.enemies.push(TrackedEnemy::new(id, enemy));
+            }
+        }
+
+        // Warn about enemies that are about to arrive from off-screen. Boss rushes don’t play
+        // through the stages, so there is nothing to warn about in them.
+        let warnings = match (&mut self.survival, &self.boss_rush) {
+            (Some(survival), _) => survival.new_warnings(),
+            (None, Some(_)) => vec![],
+            (None, None) => self.stage.new_warnings(),
+        };
+
+        for (time_until, event) in warnings {
+            self.telegraphs
+                .extend(event.spawn_telegraphs(time_until, screen));
+        }
+
+        // Once a stage is over, take a breather before moving on to the next one. Practice runs
+        // only last the one stage, and survival and boss rushes don’t play through stages at all.
+        let is_last_stage = self.stage_index + 1 == crate::Stage::all().len()
+            || matches!(self.setup.mode, RunMode::Practice(_));
+        let is_stage_finished =
+            self.stage.is_finished() && self.survival.is_none() && self.boss_rush.is_none();
+
+        if is_stage_finished && !is_last_stage {
+            match self.stage_transition {
+                None => {
+                    self.stage_transition = Some(STAGE_TRANSITION_DURATION);
+                    self.bullets.clear();
+                    self.telegraphs.clear();
+                }
+                Some(time_left) if time_left > delta_time => {
+                    self.stage_transition = Some(time_left - delta_time)
+                }
+                Some(_) => {
+                    self.stage_index += 1;
+                    self.stage = crate::StageRunner::new(self.stage_index);
+                    self.stage_transition = None;
+                }
+            }
+        }
+
+        //
+        // Enemies
+        //
+
+        // Enemies aim at whichever player is nearest to them.
+        for enemy in &mut self.enemies {
+            let id = enemy.id;
+            let target = nearest_ship_pos(&self.players, hitbox_center(enemy.hitbox()));
+            let bullets =
+                enemy.update(screen, &mut self.rng, target, &pattern_settings, delta_time);
+            self.stats.bullets_spawned += bullets.len() as u32;
+
+            self.bullets
+                .extend(bullets.into_iter().map(|bullet| bullet.with_owner(id)));
+            self.telegraphs.extend(
+                enemy
+                    .telegraphs()
+                    .into_iter()
+                    .map(|telegraph| telegraph.with_owner(id)),
+            );
+        }
+
+        // Boss attacks that run out of time end without the player getting the chance to capture
+        // them, or any other reward: the boss’s bullets vanish rather than turning into items, its
+        // warnings go with the attacks they were for, and a boss that runs out of time on its last
+        // attack retreats instead of being destroyed.
+        let mut timed_out_enemies = vec![];
+
+        for enemy in &mut self.enemies {
+            if let Some(attempt) = &mut enemy.attempt {
+                attempt.update(delta_time);
+
+                if attempt.is_timed_out() {
+                    enemy.enemy.end_boss_phase();
+                    timed_out_enemies.push(enemy.id);
+                    self.stats.attacks_timed_out += 1;
+
+                    if let (None, Some(boss_rush)) = (enemy.boss_phase(), &mut self.boss_rush) {
+                        boss_rush.record_escape();
+                    }
+                }
+            }
+        }
+
+        self.bullets.retain(|bullet| {
+            !bullet
+                .owner()
+                .is_some_and(|owner| timed_out_enemies.contains(&owner))
+        });
+        self.telegraphs.retain(|telegraph| {
+            !telegraph
+                .owner()
+                .is_some_and(|owner| timed_out_enemies.contains(&owner))
+        });
+
+        // Clearing a boss phase or destroying an enemy turns the bullets it fired into score items,
+        // and settles whether the boss attack was captured.
+        let mut cancelling_enemies = vec![];
+        let mut finished_attempts = vec![];
+
+        for enemy in &mut self.enemies {
+            let boss_phase = enemy.boss_phase();
+            let is_killed = killed_enemies.contains(&enemy.id);
+
+            if is_killed || boss_phase != enemy.boss_phase {
+                cancelling_enemies.push(enemy.id);
+                finished_attempts.extend(enemy.attempt.take());
+
+                if !is_killed {
+                    enemy.attempt = enemy.boss_attack().map(crate::AttackAttempt::new);
+                }
+            }
+
+            enemy.boss_phase = boss_phase;
+        }
+
+        self.cancel_bullets(&cancelling_enemies);
+        self.score_attack_attempts(&finished_attempts);
+
+        // Destroyed enemies are worth points to whoever finished them off, drop items, and may go
+        // out with a bang.
+        for enemy in self
+            .enemies
+            .iter()
+            .filter(|enemy| killed_enemies.contains(&enemy.id))
+        {
+            use rand::Rng;
+
+            // Survival is only scored by time survived and grazes.
+            if self.survival.is_none() {
+                let points = self.difficulty_settings.score(enemy.score_value());
+                self.players[enemy.last_hit_by.unwrap_or(0)].score += points;
+                self.rank.score(points);
+            }
+
+            let center = hitbox_center(enemy.hitbox());
+            let target = nearest_ship_pos(&self.players, center);
+
+            let bullets = enemy.die(&mut self.rng, target, &pattern_settings);
+            self.stats.bullets_spawned += bullets.len() as u32;
+            self.bullets.extend(bullets);
+
+            // Scatter the items a little so that they don’t all stack up on top of each other. A
+            // boss rush has to be got through with the lives and bombs it starts with, so anything
+            // that would top them up is dropped as points instead.
+            for kind in enemy.drops(&mut self.rng) {
+                let kind = match kind {
+                    crate::ItemKind::Power | crate::ItemKind::Bomb | crate::ItemKind::LifePiece
+                        if self.boss_rush.is_some() =>
+                    {
+                        crate::ItemKind::Point
+                    }
+                    kind => kind,
+                };
+
+                let offset = crate::Vector::new(
+                    self.rng.gen_range(-ITEM_SCATTER, ITEM_SCATTER),
+                    self.rng.gen_range(-ITEM_SCATTER, ITEM_SCATTER),
+                );
+
+                self.items.push(crate::Item::new(kind, center + offset));
+            }
+        }
+
+        // Destroyed enemies don’t go on to make the attacks they were warning about.
+        self.telegraphs.retain(|telegraph| {
+            !telegraph
+                .owner()
+                .is_some_and(|owner| killed_enemies.contains(&owner))
+        });
+
+        self.enemies
+            .retain(|enemy| !enemy.is_dead() && !enemy.has_left_screen(screen));
+
+        //
+        // Bullets
+        //
+
+        let split_bullets = move_bullets(
+            &mut self.bullets,
+            &self.players,
+            adjusted_bullet_speed,
+            screen,
+        );
+        self.stats.bullets_spawned += split_bullets.len() as u32;
+        self.bullets.extend(split_bullets);
+
+        let mut damage_taken = 0;
+
+        for player in self.players.iter_mut().filter(|player| player.is_playing()) {
+            // Ships take damage for every bullet that hits them.
+            for bullet in &self.bullets {
+                if bullet.hitbox().overlaps(&player.ship.hitbox()) {
+                    damage_taken += u32::from(player.ship.take_damage(bullet).dealt);
+                }
+            }
+
+            // Beams hurt ships for as long as they stay in them.
+            for beam in self.enemies.iter().flat_map(|enemy| enemy.beams()) {
+                if beam.hits(player.ship.hitbox()) {
+                    damage_taken += u32::from(player.ship.take_damage(beam).dealt);
+                }
+            }
+
+            // Bullets that pass close by a ship without hitting it graze it, which is worth
+            // points the first time each bullet does it.
+            let ship_hitbox = player.ship.hitbox();
+            let ship_center = hitbox_center(ship_hitbox);
+
+            for bullet in &mut self.bullets {
+                let bullet_hitbox = bullet.hitbox();
+
+                if !bullet_hitbox.overlaps(&ship_hitbox)
+                    && (hitbox_center(bullet_hitbox) - ship_center).norm() <= GRAZE_RADIUS
+                    && bullet.graze()
+                {
+                    let points = self.difficulty_settings.score(GRAZE_POINTS);
+                    player.score += points;
+                    player.grazes += 1;
+                    self.rank.score(points);
+                }
+            }
+        }
+
+        self.stats.damage_taken += damage_taken;
+
+        if damage_taken > 0 {
+            self.spoil_attack_attempts();
+        }
+
+        //
+        // Items
+        //
+
+        // Items fly to the ships when any of them goes up past the point of collection, or when
+        // one focuses near them, heading for whichever ship is nearest.
+        let point_of_collection = screen.h * POINT_OF_COLLECTION;
+        let is_above_point_of_collection = self
+            .players
+            .iter()
+            .filter(|player| player.is_playing())
+            .any(|player| player.ship.pos().y < point_of_collection);
+
+        for item in &mut self.items {
+            let is_focused_near = self
+                .players
+                .iter()
+                .filter(|player| player.is_playing() && player.is_focused)
+                .any(|player| (item.pos() - player.ship.pos()).norm() <= FOCUS_MAGNET_RADIUS);
+
+            if is_above_point_of_collection {
+                item.magnetise(true);
+            } else if is_focused_near {
+                item.magnetise(false);
+            }
+
+            item.update(nearest_ship_pos(&self.players, item.pos()), delta_time);
+        }
+
+        // Items go to the first ship that touches them.
+        let mut items = vec![];
+
+        for item in std::mem::take(&mut self.items) {
+            let collector = self
+                .players
+                .iter_mut()
+                .filter(|player| player.is_playing())
+                .find(|player| item.hitbox().overlaps(&player.ship.hitbox()));
+
+            let player = match collector {
+                Some(player) => player,
+                None => {
+                    items.push(item);
+                    continue;
+                }
+            };
+
+            if self.survival.is_none() {
+                let points = self.difficulty_settings.score(item.score_value(screen));
+                player.score += points;
+                self.rank.score(points);
+            }
+
+            player.ship.collect(item.kind());
+        }
+
+        self.items = items;
+
+        //
+        // Shift states if necessary
+        //
+
+        // Spend a life rather than dying, if there are any left, and give the ship a clear screen to
+        // come back to. Players who are out of lives sit out the rest of the run.
+        for player in &mut self.players {
+            if player.ship.is_dead() && player.ship.respawn() {
+                self.bullets.clear();
+                self.rank.die();
+            }
+        }
+
+        let is_game_over = !self.players.iter().any(Player::is_playing);
+        let is_run_complete = match &self.boss_rush {
+            Some(boss_rush) => boss_rush.is_finished(),
+            None => is_stage_finished && is_last_stage,
+        };
+
+        // Dying on the very tick the run would have been won still counts as dying.
+        if is_game_over {
+            self.outcome = Some(Outcome::Died);
+        } else if is_run_complete {
+            self.outcome = Some(Outcome::Won);
+        }
+
+        //
+        // Clean up
+        //
+
+        // Remove all bullets and lasers that aren’t on the screen. We do this because otherwise
+        // these bullets and lasers (which you can’t see anyway) are going to be continually
+        // re-rendered again and again. We also remove bullets that have hit a ship or outlived
+        // their lifetime.
+
+        let ship_hitboxes: Vec<_> = self
+            .players
+            .iter()
+            .filter(|player| player.is_playing())
+            .map(|player| player.ship.hitbox())
+            .collect();
+
+        self.bullets.retain(|bullet| {
+            bullet.is_on_screen(screen)
+                && !ship_hitboxes
+                    .iter()
+                    .any(|ship_hitbox| bullet.hitbox().overlaps(ship_hitbox))
+                && !bullet.is_expired()
+        });
+
+        for player in &mut self.players {
+            player.lasers.retain(|laser| laser.is_on_screen(screen));
+        }
+
+        self.items.retain(|item| !item.is_lost(screen));
+
+        finished_attempts
+    }
+
+    /// How hard attacks are right now, with rank and survival taken into account.
+    fn pattern_settings(&self) -> crate::DifficultySettings {
+        let pattern_settings = self.rank.apply(&self.difficulty_settings);
+
+        // Survival gets harder and harder on top of that.
+        match &self.survival {
+            Some(survival) => survival.apply(&pattern_settings),
+            None => pattern_settings,
+        }
+    }
+
+    /// Works out where the bullets and beams will be over the next `ticks` ticks, as if the ships
+    /// stayed where they are. Enemies keep moving and firing, though nothing gets destroyed. The
+    /// forecast can’t see into the future any more than a player could: enemies fire at random
+    /// with a different random number generator to the game’s, so random shots are only guesses.
+    pub fn forecast(&self, ticks: usize, screen: graphics::Rect) -> Forecast {
+        use {
+            crate::{Hitbox, OnScreen},
+            rand::SeedableRng,
+        };
+
+        let pattern_settings = self.pattern_settings();
+        let bullet_speed = pattern_settings.bullet_speed * TICK_DURATION.as_secs_f32();
+        let mut rng = crate::GameRng::seed_from_u64(u64::from(self.tick));
+        let mut enemies = self.enemies.clone();
+        let mut bullets = self.bullets.clone();
+        let mut forecast = Forecast::default();
+
+        for _ in 0..ticks {
+            for enemy in &mut enemies {
+                let target = nearest_ship_pos(&self.players, hitbox_center(enemy.hitbox()));
+                bullets.extend(enemy.update(
+                    screen,
+                    &mut rng,
+                    target,
+                    &pattern_settings,
+                    TICK_DURATION,
+                ));
+            }
+
+            let split_bullets = move_bullets(&mut bullets, &self.players, bullet_speed, screen);
+            bullets.extend(split_bullets);
+            bullets.retain(|bullet| bullet.is_on_screen(screen) && !bullet.is_expired());
+
+            forecast
+                .bullets
+                .push(bullets.iter().map(Hitbox::hitbox).collect());
+            forecast.beams.push(
+                enemies
+                    .iter()
+                    .flat_map(|enemy| enemy.beams())
+                    .cloned()
+                    .collect(),
+            );
+        }
+
+        forecast
+    }
+
+    /// Turns every bullet fired by one of the given enemies into a score item that flies to the
+    /// ship.
+    fn cancel_bullets(&mut self, owners: &[crate::EnemyId]) {
+        use crate::Position;
+
+        if owners.is_empty() {
+            return;
+        }
+
+        let (cancelled_bullets, bullets): (Vec<_>, Vec<_>) = std::mem::take(&mut self.bullets)
+            .into_iter()
+            .partition(|bullet| bullet.owner().is_some_and(|owner| owners.contains(&owner)));
+        self.bullets = bullets;
+
+        for bullet in cancelled_bullets {
+            let mut item = crate::Item::new(crate::ItemKind::Cancel, bullet.pos());
+            item.magnetise(true);
+
+            self.items.push(item);
+        }
+    }
+
+    /// Awards the capture bonus for every attempt that was captured.
+    pub fn score_attack_attempts(&mut self, attempts: &[crate::AttackAttempt]) {
+        for attempt in attempts {
+            let is_captured = attempt.is_capturable();
+
+            // Everyone still in the game shares in a capture.
+            if is_captured && self.survival.is_none() {
+                let points = self.difficulty_settings.score(CAPTURE_BONUS);
+
+                for player in self.players.iter_mut().filter(|player| player.is_playing()) {
+                    player.score += points;
+                }
+
+                self.rank.score(points);
+            }
+
+            if let Some(boss_rush) = &mut self.boss_rush {
+                boss_rush.record_attack(is_captured);
+            }
+        }
+    }
+
+    /// The score of every player put together.
+    pub fn score(&self) -> u32 {
+        self.players.iter().map(|player| player.score).sum()
+    }
+
+    /// Rules out capturing any boss attack in progress.
+    fn spoil_attack_attempts(&mut self) {
+        for attempt in self
+            .enemies
+            .iter_mut()
+            .filter_map(|enemy| enemy.attempt.as_mut())
+        {
+            attempt.spoil();
+        }
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn enemies(&self) -> &[TrackedEnemy] {
+        &self.enemies
+    }
+
+    pub fn bullets(&self) -> &[crate::Bullet] {
+        &self.bullets
+    }
+
+    pub fn telegraphs(&self) -> &[crate::Telegraph] {
+        &self.telegraphs
+    }
+
+    pub fn items(&self) -> &[crate::Item] {
+        &self.items
+    }
+
+    pub fn stages(&self) -> &'static [crate::Stage] {
+        crate::Stage::all()
+    }
+
+    /// The stage being played, or the one that was just cleared while between stages.
+    pub fn stage(&self) -> &crate::StageRunner {
+        &self.stage
+    }
+
+    /// Whether the players are taking a breather between one stage and the next.
+    pub fn is_between_stages(&self) -> bool {
+        self.stage_transition.is_some()
+    }
+
+    /// How many ticks have been played.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// How long the run has been played for.
+    pub fn time(&self) -> Duration {
+        TICK_DURATION * self.tick
+    }
+
+    pub fn setup(&self) -> RunSetup {
+        self.setup
+    }
+
+    pub fn rank(&self) -> &crate::Rank {
+        &self.rank
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn survival(&self) -> Option<&crate::SurvivalDirector> {
+        self.survival.as_ref()
+    }
+
+    pub fn boss_rush(&self) -> Option<&crate::BossRush> {
+        self.boss_rush.as_ref()
+    }
+
+    /// How the run ended, or nothing if it is still going.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    pub fn stats(&self) -> RunStats {
+        self.stats
+    }
+}
+
+/// Moves a ship for a tick the way its player is steering, slowing down to focus while they are
+/// focusing.
+pub fn steer(ship: &mut crate::Ship, input: &crate::PlayerInput, screen: graphics::Rect) {
+    use crate::{StepDistance, Velocity};
+
+    let ship_speed = if input.focus {
+        SHIP_FOCUS_SPEED
+    } else {
+        SHIP_SPEED
+    };
+    let adjusted_ship_speed = ship_speed * TICK_DURATION.as_secs_f32();
+
+    let (mut dx, mut dy) = (0.0, 0.0);
+
+    if input.up {
+        dy -= 1.0;
+    }
+    if input.down {
+        dy += 1.0;
+    }
+    if input.left {
+        dx -= 1.0;
+    }
+    if input.right {
+        dx += 1.0;
+    }
+
+    *ship.velocity_mut() = {
+        let v = crate::Vector::new(dx, dy);
+
+        // Normalising a 0.0, 0.0 vector ends up with NaN, which we want to avoid.
+        if dx == 0.0 && dy == 0.0 {
+            v
+        } else {
+            v.normalize()
+        }
+    };
+    ship.step_distance(adjusted_ship_speed);
+    ship.clamp_pos_to_screen(screen);
+}
+
+/// Moves bullets on by a tick, with homing and aimed bullets going after whichever player is
+/// nearest to them, and returns any bullets they split into.
+fn move_bullets(
+    bullets: &mut [crate::Bullet],
+    players: &[Player],
+    adjusted_bullet_speed: f32,
+    screen: graphics::Rect,
+) -> Vec<crate::Bullet> {
+    use crate::{Position, StepDistance};
+
+    let mut split_bullets = vec![];
+
+    for bullet in bullets {
+        let target = nearest_ship_pos(players, bullet.pos());
+
+        split_bullets.extend(bullet.update(TICK_DURATION, target));
+        bullet.step_distance(adjusted_bullet_speed);
+        bullet.interact_with_edges(screen);
+    }
+
+    split_bullets
+}
+
+fn hitbox_center(hitbox: graphics::Rect) -> crate::Point {
+    crate::Point::new(hitbox.x + hitbox.w / 2.0, hitbox.y + hitbox.h / 2.0)
+}
+
+/// Where the ship nearest to `pos` is, out of the players still in the game, for enemies and
+/// bullets to aim at. Once everyone is out, the first player’s ship is aimed at instead.
+fn nearest_ship_pos(players: &[Player], pos: crate::Point) -> crate::Point {
+    use crate::Position;
+
+    players
+        .iter()
+        .filter(|player| player.is_playing())
+        .map(|player| player.ship.pos())
+        .min_by(|a, b| {
+            let distance_to_a = (a - pos).norm();
+            let distance_to_b = (b - pos).norm();
+
+            distance_to_a.total_cmp(&distance_to_b)
+        })
+        .unwrap_or_else(|| players[0].ship.pos())
+}
@@ -0,0 +1,76 @@
+use {
+    rand::{RngCore, SeedableRng},
+    rand_chacha::ChaCha20Rng,
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+};
+
+/// The random number generator behind everything in a run, so that a run plays out the same way
+/// from the same seed. Unlike `rand`’s `StdRng`, its algorithm can’t change from under us, and it
+/// can be saved partway through a run and carry on from the same point once loaded.
+#[derive(Clone)]
+pub struct GameRng {
+    seed: [u8; 32],
+    rng: ChaCha20Rng,
+}
+
+/// How far through its seed’s stream of numbers the generator has got, which is all that needs
+/// saving to pick it up again.
+#[derive(Serialize, Deserialize)]
+struct SavedRng {
+    seed: [u8; 32],
+    word_pos: u64,
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl SeedableRng for GameRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+
+        // A generator that hasn’t made any numbers yet can’t tell how far through its stream it
+        // is, and overflows trying. Setting its position to the start, where it already is,
+        // puts that right without changing what it makes.
+        rng.set_word_pos(0);
+
+        Self { seed, rng }
+    }
+}
+
+impl Serialize for GameRng {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // A run would have to last for millennia to use up more than 2⁶⁴ words.
+        SavedRng {
+            seed: self.seed,
+            word_pos: self.rng.get_word_pos() as u64,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameRng {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let saved = SavedRng::deserialize(deserializer)?;
+        let mut rng = Self::from_seed(saved.seed);
+        rng.rng.set_word_pos(u128::from(saved.word_pos));
+
+        Ok(rng)
+    }
+}
@@ -0,0 +1,314 @@
+use {
+    crate::Health,
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// A heavily armed mid-boss that sways from side to side, firing fans of bullets at the ship.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Gunship {
+    pos: crate::Point,
+    movement: crate::MovementScript,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
+    health: u16,
+    max_health: u16,
+    time_since_shot: Duration,
+    volleys_fired: u32,
+    beams: Vec<crate::Beam>,
+    time_since_beam: Duration,
+    sweep_rightwards: bool,
+    /// Whether the gunship has run out of time and is flying away.
+    is_retreating: bool,
+}
+
+impl Gunship {
+    const IMG_DATA: &[u8] = include_bytes!("gunship.png");
+    /// How much health the gunship has on Normal.
+    const BASE_HEALTH: u16 = 40;
+    const ENTRY_DURATION: Duration = Duration::from_secs(2);
+    const SWAY_PERIOD: Duration = Duration::from_secs(6);
+    const BULLET_SPEED: f32 = 1.8;
+    const SHOT_COOLDOWN: Duration = Duration::from_millis(1000);
+    const BULLETS_PER_SPREAD: usize = 5;
+    const SPREAD: f32 = 0.6;
+    const BULLET_TURN_RATE: f32 = 0.8;
+    const HOMING_DURATION: Duration = Duration::from_secs(1);
+    const VOLLEYS_PER_SPLITTER: u32 = 3;
+    const SPLIT_DELAY: Duration = Duration::from_secs(1);
+    const BULLETS_PER_SPLIT: usize = 8;
+    const BULLET_COLOR: graphics::Color = graphics::Color::new(0.8, 0.4, 1.0, 1.0);
+    const BEAM_COOLDOWN: Duration = Duration::from_secs(8);
+    const BEAM_WIDTH: f32 = 16.0;
+    const BEAM_WARM_UP: Duration = Duration::from_millis(1000);
+    const BEAM_ACTIVE: Duration = Duration::from_millis(2500);
+    const BEAM_FADE_OUT: Duration = Duration::from_millis(300);
+    /// How far the beam sweeps across the screen below the gunship, in radians.
+    const BEAM_SWEEP: f32 = 1.6;
+    /// How fast the gunship flies away once it has run out of time, in pixels per second.
+    const RETREAT_SPEED: f32 = 120.0;
+    pub const ATTACKS: [crate::BossAttack; 1] = [crate::BossAttack {
+        name: "Crossfire Sweep",
+        time_limit: Duration::from_secs(40),
+    }];
+
+    /// Creates a gunship that follows its entry movement, or by default swoops down to the top
+    /// quarter of the screen, and then sways from side to side.
+    pub fn new(
+        pos: crate::Point,
+        entry: Option<crate::Movement>,
+        difficulty: &crate::DifficultySettings,
+        screen: graphics::Rect,
+    ) -> Self {
+        let entry = entry.unwrap_or(crate::Movement::MoveTo {
+            target: crate::Point::new(pos.x, screen.h / 4.0),
+            duration: Self::ENTRY_DURATION,
+            easing: crate::Easing::QuadOut,
+        });
+        let sway = crate::Movement::Hover {
+            amplitude: crate::Vector::new(screen.w / 4.0, 0.0),
+            period: Self::SWAY_PERIOD,
+            duration: None,
+        };
+
+        let max_health = difficulty.max_health(Self::BASE_HEALTH);
+
+        Self {
+            pos,
+            movement: crate::MovementScript::new(vec![entry, sway]),
+            sprite_cache: crate::SpriteCache::new(Self::IMG_DATA),
+            health: max_health,
+            max_health,
+            time_since_shot: Duration::from_millis(0),
+            volleys_fired: 0,
+            beams: vec![],
+            time_since_beam: Duration::from_millis(0),
+            sweep_rightwards: true,
+            is_retreating: false,
+        }
+    }
+
+    fn has_arrived(&self) -> bool {
+        self.movement.steps_completed() > 0
+    }
+
+    /// Moves and turns the gunship’s beams, and every so often starts sweeping a new one across
+    /// the screen, alternating which way it goes.
+    fn update_beams(&mut self, screen: graphics::Rect, delta_time: Duration) {
+        for beam in &mut self.beams {
+            beam.update(self.pos, delta_time);
+        }
+
+        self.beams.retain(|beam| !beam.is_finished());
+
+        self.time_since_beam += delta_time;
+
+        if !self.has_arrived() || self.time_since_beam < Self::BEAM_COOLDOWN {
+            return;
+        }
+
+        self.time_since_beam = Duration::from_millis(0);
+
+        let straight_down = std::f32::consts::FRAC_PI_2;
+        let (start_angle, turn_rate) = {
+            let turn_rate = Self::BEAM_SWEEP / Self::BEAM_ACTIVE.as_secs_f32();
+
+            // Angles past straight down point to the left, so sweeping rightwards means turning
+            // anticlockwise.
+            if self.sweep_rightwards {
+                (straight_down + Self::BEAM_SWEEP / 2.0, -turn_rate)
+            } else {
+                (straight_down - Self::BEAM_SWEEP / 2.0, turn_rate)
+            }
+        };
+        self.sweep_rightwards = !self.sweep_rightwards;
+
+        self.beams.push(
+            crate::Beam::new(
+                self.pos,
+                start_angle,
+                screen.w + screen.h,
+                Self::BEAM_WIDTH,
+                Self::BEAM_WARM_UP,
+                Self::BEAM_ACTIVE,
+                Self::BEAM_FADE_OUT,
+            )
+            .with_turn_rate(turn_rate),
+        );
+    }
+}
+
+impl From<Gunship> for crate::SavedEnemy {
+    fn from(enemy: Gunship) -> Self {
+        Self::Gunship(enemy)
+    }
+}
+
+impl crate::EnemyBehaviour for Gunship {
+    const SCORE_VALUE: u32 = 3000;
+    const SHOWS_HEALTH_BAR: bool = true;
+    const HIT_DROP_CHANCE: f64 = 0.05;
+
+    fn movement(&mut self, _screen: graphics::Rect, delta_time: Duration) {
+        self.movement.update(&mut self.pos, delta_time);
+    }
+
+    fn attack(
+        &mut self,
+        screen: graphics::Rect,
+        _rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet> {
+        if self.is_retreating {
+            return vec![];
+        }
+
+        self.update_beams(screen, delta_time);
+
+        self.time_since_shot += delta_time;
+
+        if !self.has_arrived() || self.time_since_shot < Self::SHOT_COOLDOWN {
+            return vec![];
+        }
+
+        self.time_since_shot = Duration::from_millis(0);
+        self.volleys_fired += 1;
+
+        // Every few volleys, lob a single bullet that bursts into a ring partway to the ship
+        // instead, so that the player can’t just stay at the bottom of the screen.
+        if self.volleys_fired % Self::VOLLEYS_PER_SPLITTER == 0 {
+            let splitter = crate::Bullet::aimed(self.pos, target, Self::BULLET_SPEED)
+                .with_shape(crate::BulletShape::LargeOrb)
+                .with_color(Self::BULLET_COLOR)
+                .with_behaviour(crate::BulletBehaviour::Split {
+                    trigger: crate::SplitTrigger::After(Self::SPLIT_DELAY),
+                    count: difficulty.bullet_count(Self::BULLETS_PER_SPLIT),
+                    spread: std::f32::consts::PI * 2.0,
+                    speed: Self::BULLET_SPEED,
+                });
+
+            return vec![splitter];
+        }
+
+        // The fan homes in on the ship for a moment after it is fired, so it can’t simply be
+        // outrun sideways.
+        crate::Bullet::spread(
+            self.pos,
+            target,
+            difficulty.bullet_count(Self::BULLETS_PER_SPREAD),
+            Self::SPREAD,
+            Self::BULLET_SPEED,
+        )
+        .into_iter()
+        .map(|bullet| {
+            bullet
+                .with_shape(crate::BulletShape::Kunai)
+                .with_color(Self::BULLET_COLOR)
+                .with_behaviour(crate::BulletBehaviour::Home {
+                    turn_rate: Self::BULLET_TURN_RATE,
+                    duration: Some(Self::HOMING_DURATION),
+                })
+        })
+        .collect()
+    }
+
+    fn beams(&self) -> &[crate::Beam] {
+        &self.beams
+    }
+
+    fn drops(&self, _rng: &mut crate::GameRng) -> Vec<crate::ItemKind> {
+        let mut drops = vec![crate::ItemKind::Power; 3];
+        drops.extend(vec![crate::ItemKind::Point; 3]);
+        drops.extend(vec![crate::ItemKind::Bomb, crate::ItemKind::LifePiece]);
+
+        drops
+    }
+
+    fn boss_phase(&self) -> Option<u16> {
+        // The gunship only has the one phase, and stops being a boss to fight once it retreats.
+        if self.is_retreating {
+            None
+        } else {
+            Some(1)
+        }
+    }
+
+    fn boss_attack(&self) -> Option<crate::BossAttack> {
+        self.boss_phase().map(|_| Self::ATTACKS[0])
+    }
+
+    fn end_boss_phase(&mut self) {
+        // The gunship only has the one phase, so running out of time means it gets away.
+        self.is_retreating = true;
+        self.beams.clear();
+        self.movement = crate::MovementScript::new(vec![crate::Movement::Linear {
+            velocity: crate::Vector::new(0.0, -Self::RETREAT_SPEED),
+        }]);
+    }
+
+    fn has_left_screen(&self, screen: graphics::Rect) -> bool {
+        use crate::OnScreen;
+
+        self.is_retreating && !self.is_on_screen(screen)
+    }
+}
+
+impl crate::Position for Gunship {
+    fn pos(&self) -> crate::Point {
+        self.pos
+    }
+
+    fn pos_mut(&mut self) -> &mut crate::Point {
+        &mut self.pos
+    }
+}
+
+impl Health for Gunship {
+    fn max_health(&self) -> u16 {
+        self.max_health
+    }
+
+    fn health(&self) -> u16 {
+        self.health
+    }
+
+    fn health_mut(&mut self) -> &mut u16 {
+        &mut self.health
+    }
+}
+
+/// The gunship’s armoured hull shrugs off half of any solid projectile.
+impl crate::TakeDamage for Gunship {
+    fn damage_multiplier(&self, kind: crate::DamageKind) -> f32 {
+        // A gunship that got away can’t be shot down for the reward after all.
+        if self.is_retreating {
+            return 0.0;
+        }
+
+        match kind {
+            crate::DamageKind::Kinetic => 0.5,
+            crate::DamageKind::Energy => 1.0,
+        }
+    }
+}
+
+impl AsRef<crate::SpriteCache> for Gunship {
+    fn as_ref(&self) -> &crate::SpriteCache {
+        &self.sprite_cache
+    }
+}
+
+impl crate::RestoreSprite for Gunship {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(Self::IMG_DATA);
+    }
+}
+
+impl crate::Hitbox for Gunship {
+    const WIDTH_MUL: f32 = 0.8;
+    const HEIGHT_MUL: f32 = 0.5;
+    const Y_OFFSET_MUL: f32 = -0.05;
+}
@@ -6,8 +6,7 @@ const HEALTHBAR_COLOR: (u8, u8, u8) = (119, 249, 169);
 const HEALTHBAR_BG_COLOR: (u8, u8, u8) = (229, 37, 72);
 
 pub trait Health {
-    const MAX_HEALTH: u16;
-
+    fn max_health(&self) -> u16;
     fn health(&self) -> u16;
     fn health_mut(&mut self) -> &mut u16;
 
@@ -33,7 +32,7 @@ impl HealthBarMesh {
 pub trait HealthBar: Health + crate::CenterPosition {
     fn health_bar(&self, ctx: &mut ggez::Context) -> ggez::GameResult<HealthBarMesh> {
         let pos = self.center_pos();
-        let health_frac: f32 = f32::from(self.health()) / f32::from(Self::MAX_HEALTH);
+        let health_frac: f32 = f32::from(self.health()) / f32::from(self.max_health());
 
         // Leave a gap the size of the healthbar between the healthbar and whatever we are
         // displaying the health of.
@@ -1,6 +1,6 @@
 use ggez::graphics;
 
-pub trait Hitbox: AsRef<graphics::Image> + crate::CenterPosition {
+pub trait Hitbox: AsRef<crate::SpriteCache> + crate::CenterPosition {
     const WIDTH_MUL: f32 = 1.0;
     const HEIGHT_MUL: f32 = 1.0;
     const X_OFFSET_MUL: f32 = 0.0;
@@ -1,9 +1,10 @@
 use ggez::graphics;
 
-pub trait ImageDrawable: crate::CenterPosition + AsRef<graphics::Image> {
+pub trait ImageDrawable: crate::CenterPosition + AsRef<crate::SpriteCache> {
     fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult {
-        graphics::draw(ctx, self.as_ref(), (self.center_pos(),))
+        let image = self.as_ref().image(ctx)?;
+        graphics::draw(ctx, image, (self.center_pos(),))
     }
 }
 
-impl<T: crate::CenterPosition + AsRef<graphics::Image>> ImageDrawable for T {}
+impl<T: crate::CenterPosition + AsRef<crate::SpriteCache>> ImageDrawable for T {}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// What a player does on one tick of the game. Moving, shooting and focusing last for as long as
+/// their keys are held, while switching shot types and using bombs happen on the tick their keys
+/// are pressed.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PlayerInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub shoot: bool,
+    pub focus: bool,
+    pub switch_shot: bool,
+    pub bomb: bool,
+}
+
+impl PlayerInput {
+    /// Packs the input into a byte, for sending over the network.
+    pub fn to_bits(self) -> u8 {
+        [
+            self.up,
+            self.down,
+            self.left,
+            self.right,
+            self.shoot,
+            self.focus,
+            self.switch_shot,
+            self.bomb,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, &is_set)| bits | (u8::from(is_set) << i))
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        let is_set = |i: u8| bits & (1 << i) != 0;
+
+        Self {
+            up: is_set(0),
+            down: is_set(1),
+            left: is_set(2),
+            right: is_set(3),
+            shoot: is_set(4),
+            focus: is_set(5),
+            switch_shot: is_set(6),
+            bomb: is_set(7),
+        }
+    }
+}
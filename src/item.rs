@@ -0,0 +1,152 @@
+use {
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemKind {
+    /// Builds up the ship’s shot power.
+    Power,
+    /// Worth more points the higher up the screen it is collected.
+    Point,
+    Bomb,
+    /// A piece of an extra life.
+    LifePiece,
+    /// A small score item left behind by a cancelled bullet.
+    Cancel,
+}
+
+impl ItemKind {
+    fn img_data(self) -> &'static [u8] {
+        match self {
+            ItemKind::Power => include_bytes!("power_item.png"),
+            ItemKind::Point => include_bytes!("point_item.png"),
+            ItemKind::Bomb => include_bytes!("bomb_item.png"),
+            ItemKind::LifePiece => include_bytes!("life_piece_item.png"),
+            ItemKind::Cancel => include_bytes!("cancel_item.png"),
+        }
+    }
+}
+
+/// Something dropped by an enemy for the ship to collect. Items pop up a little, then fall down the
+/// screen until they are either collected or lost off the bottom. Once magnetised, an item flies
+/// straight at the ship instead.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Item {
+    kind: ItemKind,
+    pos: crate::Point,
+    /// In pixels per second.
+    velocity: crate::Vector,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
+    is_magnetised: bool,
+    is_auto_collected: bool,
+}
+
+impl Item {
+    const POP_SPEED: f32 = 120.0;
+    /// In pixels per second squared.
+    const GRAVITY: f32 = 240.0;
+    const MAX_FALL_SPEED: f32 = 150.0;
+    const MAGNET_SPEED: f32 = 600.0;
+    const POWER_SCORE: u32 = 10;
+    const MAX_POINT_SCORE: u32 = 1000;
+    const MIN_POINT_SCORE: u32 = 100;
+    const CANCEL_SCORE: u32 = 10;
+
+    pub fn new(kind: ItemKind, pos: crate::Point) -> Self {
+        Self {
+            kind,
+            pos,
+            velocity: crate::Vector::new(0.0, -Self::POP_SPEED),
+            sprite_cache: crate::SpriteCache::new(kind.img_data()),
+            is_magnetised: false,
+            is_auto_collected: false,
+        }
+    }
+
+    pub fn kind(&self) -> ItemKind {
+        self.kind
+    }
+
+    /// Makes the item fly to the ship. Items that are auto-collected (because the ship went above
+    /// the point of collection) are always worth full points.
+    pub fn magnetise(&mut self, is_auto_collected: bool) {
+        self.is_magnetised = true;
+        self.is_auto_collected |= is_auto_collected;
+    }
+
+    pub fn update(&mut self, ship_pos: crate::Point, delta_time: Duration) {
+        let delta_time_secs = delta_time.as_secs_f32();
+
+        if self.is_magnetised {
+            let to_ship = ship_pos - self.pos;
+            let distance = to_ship.norm();
+            let step = Self::MAGNET_SPEED * delta_time_secs;
+
+            // Don’t overshoot the ship, which would leave the item jittering around it.
+            if distance <= step {
+                self.pos = ship_pos;
+            } else {
+                self.pos += to_ship / distance * step;
+            }
+        } else {
+            self.velocity.y =
+                (self.velocity.y + Self::GRAVITY * delta_time_secs).min(Self::MAX_FALL_SPEED);
+            self.pos += self.velocity * delta_time_secs;
+        }
+    }
+
+    /// How many points the item is worth when collected. Point items are worth the most at the
+    /// top of the screen, and the least at the bottom.
+    pub fn score_value(&self, screen: graphics::Rect) -> u32 {
+        match self.kind {
+            ItemKind::Power => Self::POWER_SCORE,
+            ItemKind::Point if self.is_auto_collected => Self::MAX_POINT_SCORE,
+            ItemKind::Point => {
+                let height = num::clamp(1.0 - self.pos.y / screen.h, 0.0, 1.0);
+                let range = (Self::MAX_POINT_SCORE - Self::MIN_POINT_SCORE) as f32;
+
+                Self::MIN_POINT_SCORE + (range * height) as u32
+            }
+            ItemKind::Cancel => Self::CANCEL_SCORE,
+            ItemKind::Bomb | ItemKind::LifePiece => 0,
+        }
+    }
+
+    /// Whether the item has fallen off the bottom of the screen and can no longer be collected.
+    pub fn is_lost(&self, screen: graphics::Rect) -> bool {
+        use crate::Hitbox;
+
+        !self.is_magnetised && self.hitbox().y > screen.y + screen.h
+    }
+}
+
+impl crate::Position for Item {
+    fn pos(&self) -> crate::Point {
+        self.pos
+    }
+
+    fn pos_mut(&mut self) -> &mut crate::Point {
+        &mut self.pos
+    }
+}
+
+impl AsRef<crate::SpriteCache> for Item {
+    fn as_ref(&self) -> &crate::SpriteCache {
+        &self.sprite_cache
+    }
+}
+
+impl crate::RestoreSprite for Item {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(self.kind.img_data());
+    }
+}
+
+/// Items are easier to pick up than they look.
+impl crate::Hitbox for Item {
+    const WIDTH_MUL: f32 = 2.0;
+    const HEIGHT_MUL: f32 = 2.0;
+}
@@ -1,19 +1,78 @@
-use {crate::Sprite, ggez::graphics};
+use {
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Laser {
     pos: crate::Point,
-    sprite_cache: graphics::Image,
+    velocity: crate::Vector,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
+    damage: u16,
+    shot_type: crate::ShotType,
 }
 
-impl<'a> Laser {
-    const IMG_DATA: &'a [u8] = include_bytes!("laser.png");
+impl Laser {
+    /// How fast homing needles turn towards their target, in radians per second.
+    const NEEDLE_TURN_RATE: f32 = 6.0;
 
-    pub fn new(pos: crate::Point, ctx: &mut ggez::Context) -> Self {
+    pub fn new(
+        pos: crate::Point,
+        velocity: crate::Vector,
+        damage: u16,
+        shot_type: crate::ShotType,
+    ) -> Self {
         Self {
             pos,
-            sprite_cache: Self::sprite(Self::IMG_DATA, ctx).unwrap(),
+            velocity,
+            sprite_cache: crate::SpriteCache::new(shot_type.img_data()),
+            damage,
+            shot_type,
         }
     }
+
+    /// Whether the laser carries on through enemies rather than being used up by the first one it
+    /// hits.
+    pub fn pierces(&self) -> bool {
+        self.shot_type == crate::ShotType::PiercingBeam
+    }
+
+    /// Turns homing lasers towards the target, if there is one. Moving the laser is still up to the
+    /// caller.
+    pub fn update(&mut self, delta_time: Duration, target: Option<crate::Point>) {
+        if let (crate::ShotType::HomingNeedles, Some(target)) = (self.shot_type, target) {
+            let homing = crate::BulletBehaviour::Home {
+                turn_rate: Self::NEEDLE_TURN_RATE,
+                duration: None,
+            };
+
+            homing.apply(
+                &mut self.velocity,
+                self.pos,
+                target,
+                Duration::from_millis(0),
+                delta_time,
+            );
+        }
+    }
+
+    /// Draws the laser turned to face the way it is flying.
+    pub fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        // Sprites point up, which is a quarter turn anticlockwise from an angle of zero.
+        let rotation = self.velocity.y.atan2(self.velocity.x) + std::f32::consts::FRAC_PI_2;
+        let image = self.sprite_cache.image(ctx)?;
+
+        graphics::draw(
+            ctx,
+            image,
+            graphics::DrawParam::new()
+                .dest(self.pos)
+                .offset(crate::Point::new(0.5, 0.5))
+                .rotation(rotation),
+        )
+    }
 }
 
 impl crate::Position for Laser {
@@ -26,16 +85,38 @@ impl crate::Position for Laser {
     }
 }
 
-impl crate::Damage for Laser {
-    const DAMAGE: u16 = 1;
+impl crate::Velocity for Laser {
+    fn velocity(&self) -> crate::Vector {
+        self.velocity
+    }
+
+    fn velocity_mut(&mut self) -> &mut crate::Vector {
+        &mut self.velocity
+    }
 }
 
-impl Sprite for Laser {}
+impl crate::StepDistance for Laser {}
+
+impl crate::Damage for Laser {
+    fn damage(&self) -> u16 {
+        self.damage
+    }
 
-impl AsRef<graphics::Image> for Laser {
-    fn as_ref(&self) -> &graphics::Image {
+    fn damage_kind(&self) -> crate::DamageKind {
+        self.shot_type.damage_kind()
+    }
+}
+
+impl AsRef<crate::SpriteCache> for Laser {
+    fn as_ref(&self) -> &crate::SpriteCache {
         &self.sprite_cache
     }
 }
 
+impl crate::RestoreSprite for Laser {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(self.shot_type.img_data());
+    }
+}
+
 impl crate::Hitbox for Laser {}
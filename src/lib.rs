@@ -1,30 +1,88 @@
+mod beam;
+mod boss_attack;
+mod boss_rush;
+mod bot;
 mod bullet;
+mod bullet_behaviour;
+mod bullet_shape;
 mod damage;
+mod debug_overlay;
+mod difficulty;
+mod edge_policy;
+mod enemy;
+mod game;
+mod game_rng;
+mod gunship;
 mod health;
 mod hitbox;
 mod image_drawable;
+mod input;
+mod item;
 mod laser;
+mod movement;
+mod netcode;
 mod on_screen;
+mod popcorn_fighter;
 mod position;
+mod practice;
+mod rank;
+mod replay;
 mod ship;
+mod shot_type;
 mod sky_core;
 mod sprite;
+mod stage;
 mod step_distance;
+mod survival;
+mod telegraph;
+mod turret;
 mod velocity;
 
 pub use {
+    beam::{Beam, BeamPhase},
+    boss_attack::{AttackAttempt, BossAttack, CaptureHistory, CaptureRecord},
+    boss_rush::{BossResult, BossRush, BossRushLeaderboard, LeaderboardEntry},
+    bot::Bot,
     bullet::Bullet,
-    damage::{Damage, TakeDamage},
+    bullet_behaviour::{BulletBehaviour, NewDirection, SplitTrigger},
+    bullet_shape::BulletShape,
+    damage::{Damage, DamageKind, DamageOutcome, TakeDamage},
+    debug_overlay::{DebugOverlay, DebugStats},
+    difficulty::{Difficulty, DifficultySettings},
+    edge_policy::EdgePolicy,
+    enemy::{Enemy, EnemyBehaviour, EnemyId, SavedEnemy},
+    game::{
+        steer, Forecast, Game, Outcome, Player, Practice, RunMode, RunSetup, RunStats,
+        TrackedEnemy, MAX_PLAYERS, PLAYFIELD, TICKS_PER_SECOND, TICK_DURATION,
+    },
+    game_rng::GameRng,
+    gunship::Gunship,
     health::{Health, HealthBar},
     hitbox::Hitbox,
     image_drawable::ImageDrawable,
+    input::PlayerInput,
+    item::{Item, ItemKind},
     laser::Laser,
+    movement::{Easing, Movement, MovementScript, Path},
+    netcode::{Netplay, NetworkShim, RollbackSession, Transport, UdpTransport, MAX_PREDICTION},
     on_screen::OnScreen,
+    popcorn_fighter::PopcornFighter,
     position::{CenterPosition, Position},
+    practice::PracticeStart,
+    rank::Rank,
+    replay::Replay,
     ship::Ship,
+    shot_type::ShotType,
     sky_core::SkyCore,
-    sprite::Sprite,
+    sprite::{RestoreSprite, SpriteCache},
+    stage::{
+        Curve, EnemyKind, Formation, ScreenFraction, Stage, StageEvent, StageRunner, TimedEvent,
+        WavePath,
+    },
     step_distance::StepDistance,
+    survival::SurvivalDirector,
+    telegraph::{Telegraph, TelegraphKind},
+    turret::Turret,
     velocity::Velocity,
 };
 
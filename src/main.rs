@@ -1,28 +1,374 @@
 use {
     ggez::{event, graphics},
-    std::time::Duration,
+    std::{
+        collections::{HashSet, VecDeque},
+        time::{Duration, Instant},
+    },
 };
 
 const SKY_COLOR: (u8, u8, u8) = (154, 188, 245);
-const SHIP_SPEED: f32 = 200.0;
-const LASER_SPEED: f32 = 900.0;
-const BULLET_SPEED: f32 = 100.0;
-const SKY_CORE_SPEED: f32 = 10.0;
-const LASER_COOLDOWN: Duration = Duration::from_millis(250);
-const SHIP_OFFSET_FROM_BOTTOM: f32 = 100.0;
 const FROZEN_SCREEN_FONT_SIZE: f32 = 150.0;
+const SCORE_FONT_SIZE: f32 = 32.0;
+const SCORE_MARGIN: f32 = 16.0;
+const SHOT_FONT_SIZE: f32 = 20.0;
+const MENU_TITLE_FONT_SIZE: f32 = 64.0;
+const MENU_FONT_SIZE: f32 = 32.0;
+/// The most lives a practice run can start with.
+const PRACTICE_MAX_LIVES: u8 = 8;
+const BOSS_ATTACK_FONT_SIZE: f32 = 24.0;
+const RESULTS_FONT_SIZE: f32 = 32.0;
+const LEADERBOARD_FONT_SIZE: f32 = 24.0;
+const BANNER_FONT_SIZE: f32 = 64.0;
+const DIALOGUE_FONT_SIZE: f32 = 24.0;
+const DIALOGUE_BOX_HEIGHT: f32 = 96.0;
+const DIALOGUE_BOX_ALPHA: f32 = 0.6;
+const DIALOGUE_MARGIN: f32 = 16.0;
+const STAGE_TITLE_DURATION: Duration = Duration::from_secs(3);
+/// Where a run is saved when the game is quit partway through it, to be carried on with the next
+/// time the game starts.
+const SUSPENDED_RUN_FILE: &str = "suspended_run.ron";
+const QUICK_SAVE_FILE: &str = "quick_save.ron";
+/// Where the last finished run is saved, to be played again in the headless simulator.
+const LAST_REPLAY_FILE: &str = "last_replay.ron";
+/// Hold to go back in time, in practice runs.
+const REWIND_KEY: event::KeyCode = event::KeyCode::Back;
+/// How far back a run can be rewound.
+const REWIND_TICKS: usize = 5 * game_test::TICKS_PER_SECOND as usize;
+
+/// The keys a player flies their ship with.
+#[derive(Copy, Clone)]
+struct Controls {
+    up: event::KeyCode,
+    down: event::KeyCode,
+    left: event::KeyCode,
+    right: event::KeyCode,
+    shoot: event::KeyCode,
+    focus: event::KeyCode,
+    switch_shot: event::KeyCode,
+    bomb: event::KeyCode,
+}
+
+/// The controls for each player, one set for every player a run can have.
+const PLAYER_CONTROLS: [Controls; game_test::MAX_PLAYERS] = [
+    Controls {
+        up: event::KeyCode::W,
+        down: event::KeyCode::S,
+        left: event::KeyCode::A,
+        right: event::KeyCode::D,
+        shoot: event::KeyCode::Space,
+        focus: event::KeyCode::LShift,
+        switch_shot: event::KeyCode::X,
+        bomb: event::KeyCode::B,
+    },
+    Controls {
+        up: event::KeyCode::Up,
+        down: event::KeyCode::Down,
+        left: event::KeyCode::Left,
+        right: event::KeyCode::Right,
+        shoot: event::KeyCode::RControl,
+        focus: event::KeyCode::RShift,
+        switch_shot: event::KeyCode::Period,
+        bomb: event::KeyCode::Slash,
+    },
+];
+
+impl Controls {
+    /// What the player is holding down. Shot switches and bombs are picked up as their keys are
+    /// pressed instead, so that quick taps aren’t missed between ticks.
+    fn held_input(&self, keys: &HashSet<event::KeyCode>) -> game_test::PlayerInput {
+        game_test::PlayerInput {
+            up: keys.contains(&self.up),
+            down: keys.contains(&self.down),
+            left: keys.contains(&self.left),
+            right: keys.contains(&self.right),
+            shoot: keys.contains(&self.shoot),
+            focus: keys.contains(&self.focus),
+            ..Default::default()
+        }
+    }
+}
 
 struct MainState {
-    ship: game_test::Ship,
-    lasers: Vec<game_test::Laser>,
-    sky_core: game_test::SkyCore,
-    bullets: Vec<game_test::Bullet>,
-    time_since_shot_laser: Duration,
+    game: game_test::Game,
     state: State,
+    capture_history: game_test::CaptureHistory,
+    leaderboard: game_test::BossRushLeaderboard,
+    /// Where the finished boss rush placed on the leaderboard, if it made it on.
+    leaderboard_place: Option<usize>,
+    debug_overlay: game_test::DebugOverlay,
+    /// The shot switches and bombs each player has pressed for since the last tick.
+    pressed_actions: [game_test::PlayerInput; PLAYER_CONTROLS.len()],
+    /// Every player’s input on each tick of the run since `recording_start`, along with the rank.
+    /// Rewinding takes back what was recorded for the ticks that were undone, so that the
+    /// recording carries on from the rewound point.
+    recording: game_test::Replay,
+    /// The tick the recording starts from, which is only the start of the run if it wasn’t carried
+    /// on from a save.
+    recording_start: u32,
+    /// How the game was at the start of each of the last few seconds’ worth of ticks, newest last,
+    /// for rewinding through.
+    rewind_buffer: VecDeque<game_test::Game>,
+    /// Whether the run has been rewound or quick-loaded since it started.
+    has_gone_back: bool,
+    /// The connection to the other player, in an online co-op game.
+    netplay: Option<game_test::Netplay<Box<dyn game_test::Transport>>>,
+}
+
+/// How to connect to the other player for online co-op, as given on the command line. Both
+/// games need to be given the same seed and input delay. The latency and loss make the network
+/// worse than it really is, for trying out netplay between two games on the same machine.
+struct NetplayOptions {
+    local_addr: String,
+    peer_addr: String,
+    local_player: usize,
+    seed: u64,
+    input_delay: u32,
+    latency: Duration,
+    loss: f64,
+}
+
+/// Shown when the command line can’t be read, with an example of trying out netplay between two
+/// games on the same machine over a network that is slow and loses a tenth of its packets.
+const NETPLAY_USAGE: &str = "usage: game-test [--netplay <local address> <peer address> <1 or 2> \
+[--seed <seed>] [--input-delay <ticks>] [--latency <milliseconds>] [--loss <chance>]]
+
+For example, to play both ends of an online game on one machine, run each of these in its own \
+terminal:
+    game-test --netplay 127.0.0.1:7000 127.0.0.1:7001 1 --seed 5 --latency 100 --loss 0.1
+    game-test --netplay 127.0.0.1:7001 127.0.0.1:7000 2 --seed 5 --latency 100 --loss 0.1";
+/// How many ticks local inputs are held back by in online co-op, unless told otherwise.
+const DEFAULT_INPUT_DELAY: u32 = 2;
+
+impl NetplayOptions {
+    /// Reads the options from the command line arguments (without the program name), or gives
+    /// back `None` if the game isn’t being played online.
+    fn from_args(mut args: impl Iterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        match args.next() {
+            Some(arg) if arg == "--netplay" => (),
+            Some(arg) => anyhow::bail!("unexpected argument ‘{}’", arg),
+            None => return Ok(None),
+        }
+
+        let mut next_arg = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow::anyhow!("missing the {}", name))
+        };
+
+        let local_addr = next_arg("local address")?;
+        let peer_addr = next_arg("peer address")?;
+        let local_player = match next_arg("player number")?.as_str() {
+            "1" => 0,
+            "2" => 1,
+            player => anyhow::bail!("there is no player ‘{}’, only 1 and 2", player),
+        };
+
+        let mut options = Self {
+            local_addr,
+            peer_addr,
+            local_player,
+            seed: 0,
+            input_delay: DEFAULT_INPUT_DELAY,
+            latency: Duration::from_millis(0),
+            loss: 0.0,
+        };
+
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("missing a value for {}", flag))?;
+
+            match flag.as_str() {
+                "--seed" => options.seed = value.parse()?,
+                "--input-delay" => options.input_delay = value.parse()?,
+                "--latency" => options.latency = Duration::from_millis(value.parse()?),
+                "--loss" => options.loss = num::clamp(value.parse()?, 0.0, 1.0),
+                _ => anyhow::bail!("unexpected argument ‘{}’", flag),
+            }
+        }
+
+        Ok(Some(options))
+    }
+}
+
+/// The menu for setting up a run. Picking anywhere to start other than the beginning of the game
+/// makes the run a practice run.
+#[derive(Copy, Clone)]
+struct Menu {
+    row: MenuRow,
+    difficulty: game_test::Difficulty,
+    start: MenuStart,
+    is_co_op: bool,
+    lives: u8,
+    bombs: u8,
+    power_level: u8,
+}
+
+/// The choices of where to start on the menu, in the order they are cycled through.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum MenuStart {
+    FullGame,
+    Survival,
+    BossRush,
+    /// An index into the practice start points.
+    Practice(usize),
+}
+
+impl MenuStart {
+    fn index(self) -> usize {
+        match self {
+            MenuStart::FullGame => 0,
+            MenuStart::Survival => 1,
+            MenuStart::BossRush => 2,
+            MenuStart::Practice(index) => index + 3,
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => MenuStart::FullGame,
+            1 => MenuStart::Survival,
+            2 => MenuStart::BossRush,
+            _ => MenuStart::Practice(index - 3),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum MenuRow {
+    Difficulty,
+    Players,
+    Start,
+    Lives,
+    Bombs,
+    Power,
+}
+
+impl Menu {
+    /// Creates a menu with the choices from the given setup already made. The loadout defaults to
+    /// the difficulty’s starting lives and bombs at full power.
+    fn new(setup: game_test::RunSetup, starts: &[game_test::PracticeStart]) -> Self {
+        match setup.mode {
+            game_test::RunMode::Practice(practice) => Self {
+                row: MenuRow::Difficulty,
+                difficulty: setup.difficulty,
+                start: starts
+                    .iter()
+                    .position(|&start| start == practice.start)
+                    .map_or(MenuStart::FullGame, MenuStart::Practice),
+                is_co_op: setup.is_co_op,
+                lives: practice.lives,
+                bombs: practice.bombs,
+                power_level: practice.power_level,
+            },
+            mode => {
+                let settings = setup
+                    .difficulty
+                    .settings()
+                    .expect("invalid difficulty data");
+
+                Self {
+                    row: MenuRow::Difficulty,
+                    difficulty: setup.difficulty,
+                    start: match mode {
+                        game_test::RunMode::Survival => MenuStart::Survival,
+                        game_test::RunMode::BossRush => MenuStart::BossRush,
+                        _ => MenuStart::FullGame,
+                    },
+                    is_co_op: setup.is_co_op,
+                    lives: settings.starting_lives,
+                    bombs: settings.starting_bombs,
+                    power_level: game_test::ShotType::MAX_POWER_LEVEL,
+                }
+            }
+        }
+    }
+
+    fn setup(&self, starts: &[game_test::PracticeStart]) -> game_test::RunSetup {
+        game_test::RunSetup {
+            difficulty: self.difficulty,
+            is_co_op: self.is_co_op,
+            mode: match self.start {
+                MenuStart::FullGame => game_test::RunMode::FullGame,
+                MenuStart::Survival => game_test::RunMode::Survival,
+                MenuStart::BossRush => game_test::RunMode::BossRush,
+                MenuStart::Practice(index) => game_test::RunMode::Practice(game_test::Practice {
+                    start: starts[index],
+                    lives: self.lives,
+                    bombs: self.bombs,
+                    power_level: self.power_level,
+                }),
+            },
+        }
+    }
+
+    /// The rows that can be picked, which only include the loadout for practice runs.
+    fn rows(&self) -> &'static [MenuRow] {
+        match self.start {
+            MenuStart::Practice(_) => &[
+                MenuRow::Difficulty,
+                MenuRow::Players,
+                MenuRow::Start,
+                MenuRow::Lives,
+                MenuRow::Bombs,
+                MenuRow::Power,
+            ],
+            _ => &[MenuRow::Difficulty, MenuRow::Players, MenuRow::Start],
+        }
+    }
+
+    fn select_next_row(&mut self, is_forwards: bool) {
+        let rows = self.rows();
+        let index = rows.iter().position(|&row| row == self.row).unwrap_or(0);
+
+        self.row = if is_forwards {
+            rows[(index + 1).min(rows.len() - 1)]
+        } else {
+            rows[index.saturating_sub(1)]
+        };
+    }
+
+    /// Changes the choice on the selected row to the next or previous one.
+    fn change_choice(&mut self, is_forwards: bool, start_count: usize) {
+        let step = |value: u8, min: u8, max: u8| {
+            if is_forwards {
+                (value + 1).min(max)
+            } else {
+                value.saturating_sub(1).max(min)
+            }
+        };
+
+        match self.row {
+            MenuRow::Difficulty => {
+                self.difficulty = if is_forwards {
+                    self.difficulty.harder()
+                } else {
+                    self.difficulty.easier()
+                }
+            }
+            MenuRow::Players => self.is_co_op = is_forwards,
+            MenuRow::Start => {
+                // The full game and the other modes come before the practice start points.
+                let index = self.start.index();
+                let last_index = start_count + 2;
+
+                self.start = MenuStart::from_index(if is_forwards {
+                    (index + 1).min(last_index)
+                } else {
+                    index.saturating_sub(1)
+                })
+            }
+            MenuRow::Lives => self.lives = step(self.lives, 0, PRACTICE_MAX_LIVES),
+            MenuRow::Bombs => self.bombs = step(self.bombs, 0, game_test::Ship::MAX_BOMBS),
+            MenuRow::Power => {
+                self.power_level = step(self.power_level, 1, game_test::ShotType::MAX_POWER_LEVEL)
+            }
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
 enum State {
+    Menu(Menu),
     Playing,
     Frozen {
         state: FrozenState,
@@ -38,43 +384,110 @@ enum FrozenState {
 }
 
 impl MainState {
+    /// Starts off on the menu, unless a run was suspended when the game was last quit, in which case
+    /// that run carries on.
     fn new(ctx: &mut ggez::Context) -> Self {
-        let screen_dimens = graphics::screen_coordinates(ctx);
+        let suspended_run = ggez::filesystem::user_data_dir(ctx).join(SUSPENDED_RUN_FILE);
+
+        // Suspended runs are only resumed the once, so that one that can’t be loaded isn’t tried
+        // again every time the game starts.
+        if suspended_run.exists() {
+            let game = game_test::Game::load(&suspended_run);
+
+            if let Err(error) = std::fs::remove_file(&suspended_run) {
+                eprintln!("Failed to remove the suspended run: {}", error);
+            }
+
+            match game {
+                Ok(game) => return Self::with_game(game, ctx),
+                Err(error) => eprintln!("Failed to resume the suspended run: {}", error),
+            }
+        }
+
+        let game = game_test::Game::new(rand::random(), Default::default(), game_test::PLAYFIELD);
+        let mut main_state = Self::with_game(game, ctx);
+        main_state.open_menu();
+
+        main_state
+    }
+
+    fn with_game(game: game_test::Game, ctx: &mut ggez::Context) -> Self {
+        let data_dir = ggez::filesystem::user_data_dir(ctx);
+
+        // Runs carried on from a save are only recorded from where they were carried on from.
+        let recording = game_test::Replay::new(&game);
+        let recording_start = game.tick();
 
         Self {
-            ship: game_test::Ship::new(
-                game_test::Point::new(
-                    screen_dimens.w / 2.0,
-                    screen_dimens.h - SHIP_OFFSET_FROM_BOTTOM,
-                ),
-                ctx,
-            ),
-            lasers: vec![],
-            sky_core: game_test::SkyCore::new(ctx),
-            bullets: vec![],
-            // If the time since the laser was last shot is the laser cooldown, then this means that
-            // we can start shooting immediately
-            time_since_shot_laser: LASER_COOLDOWN,
+            game,
             state: State::Playing,
+            // A missing or unreadable history shouldn’t stop the game from starting.
+            capture_history: game_test::CaptureHistory::load(data_dir).unwrap_or_default(),
+            leaderboard: game_test::BossRushLeaderboard::load(data_dir).unwrap_or_default(),
+            leaderboard_place: None,
+            debug_overlay: Default::default(),
+            pressed_actions: Default::default(),
+            recording,
+            recording_start,
+            rewind_buffer: VecDeque::new(),
+            has_gone_back: false,
+            netplay: None,
         }
     }
 
-    fn update_playing(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
-        use {
-            game_test::{Health, Hitbox, OnScreen, Position, StepDistance, TakeDamage, Velocity},
-            ggez::input::keyboard,
-            rand::Rng,
+    /// Starts an online co-op game of the full game straight away, skipping the menu.
+    fn with_netplay(options: NetplayOptions, ctx: &mut ggez::Context) -> anyhow::Result<Self> {
+        let transport =
+            game_test::UdpTransport::bind(options.local_addr.as_str(), options.peer_addr.as_str())?;
+        let transport: Box<dyn game_test::Transport> =
+            if options.latency > Duration::from_millis(0) || options.loss > 0.0 {
+                Box::new(game_test::NetworkShim::new(
+                    transport,
+                    options.latency,
+                    options.loss,
+                ))
+            } else {
+                Box::new(transport)
+            };
+
+        let setup = game_test::RunSetup {
+            is_co_op: true,
+            ..Default::default()
         };
+        let game = game_test::Game::new(options.seed, setup, game_test::PLAYFIELD);
+        let mut main_state = Self::with_game(game, ctx);
+        main_state.netplay = Some(game_test::Netplay::new(game_test::RollbackSession::new(
+            transport,
+            options.local_player,
+            options.input_delay,
+        )));
 
-        // The longer between each frame, the further things move. This compensates for any
-        // changes in FPS.
-        let delta_time = ggez::timer::delta(ctx);
-        let delta_time_secs = delta_time.as_secs_f32();
+        Ok(main_state)
+    }
+
+    /// The keys a player plays with on this machine. In an online game, the local player always
+    /// uses the first player’s keys.
+    fn controls(&self, index: usize) -> Controls {
+        if self.netplay.is_some() {
+            PLAYER_CONTROLS[0]
+        } else {
+            PLAYER_CONTROLS[index]
+        }
+    }
+
+    /// What a player on this machine is doing, counting the presses since the last tick.
+    fn input(&self, index: usize, keys: &HashSet<event::KeyCode>) -> game_test::PlayerInput {
+        let pressed = self.pressed_actions[index];
 
-        let adjusted_ship_speed = SHIP_SPEED * delta_time_secs;
-        let adjusted_laser_speed = LASER_SPEED * delta_time_secs;
-        let adjusted_bullet_speed = BULLET_SPEED * delta_time_secs;
-        let adjusted_sky_core_speed = SKY_CORE_SPEED * delta_time_secs;
+        game_test::PlayerInput {
+            switch_shot: pressed.switch_shot,
+            bomb: pressed.bomb,
+            ..self.controls(index).held_input(keys)
+        }
+    }
+
+    fn update_playing(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        use ggez::input::keyboard;
 
         let keys = keyboard::pressed_keys(ctx);
 
@@ -90,135 +503,225 @@ impl MainState {
             return Ok(());
         }
 
-        //
-        // Ship
-        //
+        // Holding the rewind key plays the run backwards, a tick at a time.
+        if keys.contains(&REWIND_KEY) && self.can_use_practice_tools() {
+            self.rewind();
+            return Ok(());
+        }
 
-        // Move ship with WASD.
-        let (mut dx, mut dy) = (0.0, 0.0);
+        let inputs: Vec<_> = (0..self.game.players().len())
+            .map(|index| self.input(index, keys))
+            .collect();
+        self.pressed_actions = Default::default();
 
-        if keys.contains(&keyboard::KeyCode::W) {
-            dy -= 1.0;
+        // Keeping enough snapshots to rewind through is only worth it when rewinding is allowed.
+        if self.can_use_practice_tools() {
+            self.rewind_buffer.push_back(self.game.clone());
+
+            if self.rewind_buffer.len() > REWIND_TICKS {
+                self.rewind_buffer.pop_front();
+            }
+        } else {
+            self.rewind_buffer.clear();
         }
-        if keys.contains(&keyboard::KeyCode::S) {
-            dy += 1.0;
+
+        self.recording.record(&self.game, inputs.clone());
+
+        let finished_attempts = self.game.update(&inputs, game_test::PLAYFIELD);
+        self.record_attack_attempts(&finished_attempts, ctx);
+        self.end_run_if_over(ctx);
+
+        Ok(())
+    }
+
+    /// Plays an online game on by a tick, if the other player isn’t too far behind. Online games
+    /// can’t be paused, and keep in touch with the other player even once they are over, so that
+    /// the other player can finish too.
+    fn update_netplay(&mut self, ctx: &mut ggez::Context) {
+        use ggez::input::keyboard;
+
+        let input = self.input(0, keyboard::pressed_keys(ctx));
+        let netplay = match &mut self.netplay {
+            Some(netplay) => netplay,
+            None => return,
+        };
+
+        // Lost or garbled packets don’t matter, since every packet repeats the inputs before it.
+        if let Err(error) = netplay.poll() {
+            eprintln!("Failed to hear from the other player: {}", error);
         }
-        if keys.contains(&keyboard::KeyCode::A) {
-            dx -= 1.0;
+
+        // Carrying on without rolling back leaves the games out of step, but that is still better
+        // than crashing.
+        if let Err(error) = netplay.roll_back(&mut self.game) {
+            eprintln!("Failed to roll back: {}", error);
         }
-        if keys.contains(&keyboard::KeyCode::D) {
-            dx += 1.0;
+
+        if netplay.advance(&mut self.game, input) {
+            self.pressed_actions = Default::default();
         }
 
-        *self.ship.velocity_mut() = {
-            let v = game_test::Vector::new(dx, dy);
+        // Unheard inputs are sent again every frame, even while waiting for the other player.
+        if let Err(error) = netplay.send_pending() {
+            eprintln!("Failed to send inputs to the other player: {}", error);
+        }
 
-            // Normalising a 0.0, 0.0 vector ends up with NaN, which we want to avoid.
-            if dx == 0.0 && dy == 0.0 {
-                v
-            } else {
-                v.normalize()
+        // Until every input from the other player has arrived, a rollback could still change how
+        // the run ended.
+        if netplay.session().is_confirmed() {
+            if let State::Playing = self.state {
+                self.end_run_if_over(ctx);
             }
-        };
-        self.ship.step_distance(adjusted_ship_speed);
-        self.ship.clamp_pos_to_screen(ctx);
+        }
+    }
 
-        //
-        // Lasers
-        //
+    /// Saves the run in progress, so that it can be carried on with the next time the game starts.
+    /// Online games can’t be carried on alone, so they aren’t saved.
+    fn suspend(&self, ctx: &ggez::Context) {
+        let is_in_progress = matches!(
+            self.state,
+            State::Playing
+                | State::Frozen {
+                    state: FrozenState::Paused,
+                    ..
+                }
+        );
 
-        // Fire lasers with space if the cooldown has finished.
-        if keys.contains(&keyboard::KeyCode::Space) && self.time_since_shot_laser >= LASER_COOLDOWN
-        {
-            self.lasers.push(self.ship.shoot(ctx));
-            self.time_since_shot_laser = Duration::from_millis(0);
-        } else {
-            self.time_since_shot_laser += delta_time;
+        if !is_in_progress || self.netplay.is_some() {
+            return;
         }
 
-        // Make lasers move up the screen.
-        for laser in &mut self.lasers {
-            laser.pos_mut().y -= adjusted_laser_speed;
+        let path = ggez::filesystem::user_data_dir(ctx).join(SUSPENDED_RUN_FILE);
+
+        if let Err(error) = self.game.save(&path) {
+            eprintln!("Failed to suspend the run: {}", error);
         }
+    }
 
-        // Let the Sky Core take damage for every laser that hits it.
-        for laser in &self.lasers {
-            if laser.hitbox().overlaps(&self.sky_core.hitbox()) {
-                self.sky_core.take_damage(laser);
-            }
+    /// Practice runs can be quick-saved, loaded again and rewound, to keep going back to the same
+    /// moment. Debug builds can do it in any run while the debug overlay is open, to make bugs
+    /// easy to reproduce. Online games can’t, since the other player would have to go back too.
+    fn can_use_practice_tools(&self) -> bool {
+        let is_allowed = matches!(self.game.setup().mode, game_test::RunMode::Practice(_))
+            || cfg!(debug_assertions) && self.debug_overlay.is_visible();
+
+        self.netplay.is_none() && is_allowed
+    }
+
+    /// Goes back a tick, if there are any left to go back to.
+    fn rewind(&mut self) {
+        if let Some(game) = self.rewind_buffer.pop_back() {
+            self.game = game;
+            self.has_gone_back = true;
+            self.recording
+                .truncate((self.game.tick() - self.recording_start) as usize);
         }
+    }
 
-        //
-        // Sky Core
-        //
+    fn quick_save(&self, ctx: &ggez::Context) {
+        let path = ggez::filesystem::user_data_dir(ctx).join(QUICK_SAVE_FILE);
 
-        // Shoot a bullet from the Sky Core one in two cycles.
-        if rand::thread_rng().gen_range(0, 2) == 0 {
-            self.bullets.push(self.sky_core.shoot(ctx));
+        if let Err(error) = self.game.save(&path) {
+            eprintln!("Failed to quick-save: {}", error);
         }
+    }
 
-        // Stop the Sky Core from its march down the screen once it reaches the centre.
-        let is_sky_core_before_vertical_center =
-            self.sky_core.pos().y < graphics::screen_coordinates(ctx).h / 2.0;
+    fn quick_load(&mut self, ctx: &mut ggez::Context) {
+        let path = ggez::filesystem::user_data_dir(ctx).join(QUICK_SAVE_FILE);
 
-        if is_sky_core_before_vertical_center {
-            self.sky_core.step_distance(adjusted_sky_core_speed);
+        match game_test::Game::load(&path) {
+            Ok(game) => {
+                self.game = game;
+                self.pressed_actions = Default::default();
+                // Neither the recording nor the ticks to rewind through lead up to the loaded game.
+                self.recording = game_test::Replay::new(&self.game);
+                self.recording_start = self.game.tick();
+                self.rewind_buffer.clear();
+                self.has_gone_back = true;
+            }
+            Err(error) => eprintln!("Failed to load the quick save: {}", error),
         }
+    }
 
-        //
-        // Bullets
-        //
+    /// Freezes the screen once the run is over.
+    fn end_run_if_over(&mut self, ctx: &ggez::Context) {
+        let state = match self.game.outcome() {
+            Some(game_test::Outcome::Died) => FrozenState::Died,
+            Some(game_test::Outcome::Won) => FrozenState::Won,
+            None => return,
+        };
+
+        self.state = State::Frozen {
+            state,
+            overlay_alpha: 0.0,
+        };
+        self.record_boss_rush(ctx);
+        self.save_replay(ctx);
+    }
 
-        for bullet in &mut self.bullets {
-            bullet.step_distance(adjusted_bullet_speed);
+    /// Saves the run that just finished as a replay, as long as every tick of it was recorded.
+    /// Runs that were carried on from a save weren’t recorded from the start, and online games
+    /// aren’t recorded at all.
+    fn save_replay(&self, ctx: &ggez::Context) {
+        if self.recording_start != 0 || self.recording.inputs.len() != self.game.tick() as usize {
+            return;
         }
 
-        // The ship takes damage for every bullet that hits it.
-        for bullet in &self.bullets {
-            if bullet.hitbox().overlaps(&self.ship.hitbox()) {
-                self.ship.take_damage(bullet);
-            }
+        let path = ggez::filesystem::user_data_dir(ctx).join(LAST_REPLAY_FILE);
+
+        if let Err(error) = self.recording.save(&path) {
+            eprintln!("Failed to save the replay: {}", error);
         }
+    }
 
-        //
-        // Shift states if necessary
-        //
+    /// Adds finished boss attacks to the capture history. Only attempts from full runs played
+    /// straight through count, since practice, survival and boss rushes meet the bosses in other
+    /// ways, and going back lets an attack be tried again until it is captured.
+    fn record_attack_attempts(
+        &mut self,
+        attempts: &[game_test::AttackAttempt],
+        ctx: &ggez::Context,
+    ) {
+        let is_full_run = matches!(self.game.setup().mode, game_test::RunMode::FullGame);
 
-        match (self.ship.is_dead(), self.sky_core.is_dead()) {
-            (true, true) => panic!("sda"),
-            (true, _) => {
-                self.state = State::Frozen {
-                    state: FrozenState::Died,
-                    overlay_alpha: 0.0,
-                }
-            }
-            (_, true) => {
-                self.state = State::Frozen {
-                    state: FrozenState::Won,
-                    overlay_alpha: 0.0,
-                }
-            }
-            _ => (),
+        if attempts.is_empty() || !is_full_run || self.has_gone_back {
+            return;
         }
 
-        //
-        // Clean up
-        //
+        for attempt in attempts {
+            self.capture_history.record(
+                self.game.setup().difficulty,
+                attempt.attack(),
+                attempt.is_capturable(),
+            );
+        }
 
-        // Remove all bullets and lasers that aren’t on the screen. We do this because otherwise
-        // these bullets and lasers (which you can’t see anyway) are going to be continually
-        // re-rendered again and again. We also remove lasers that have hit the Sky Core, and
-        // bullets that have hit the ship.
+        let data_dir = ggez::filesystem::user_data_dir(ctx);
 
-        let ship_hitbox = self.ship.hitbox();
-        let sky_core_hitbox = self.sky_core.hitbox();
+        if let Err(error) = self.capture_history.save(data_dir) {
+            eprintln!("Failed to save the capture history: {}", error);
+        }
+    }
 
-        self.bullets
-            .retain(|bullet| bullet.is_on_screen(ctx) && !bullet.hitbox().overlaps(&ship_hitbox));
-        self.lasers
-            .retain(|laser| laser.is_on_screen(ctx) && !laser.hitbox().overlaps(&sky_core_hitbox));
+    /// Puts a finished boss rush on the leaderboard, if it is good enough.
+    fn record_boss_rush(&mut self, ctx: &ggez::Context) {
+        let boss_rush = match self.game.boss_rush() {
+            Some(boss_rush) => boss_rush,
+            None => return,
+        };
 
-        Ok(())
+        let entry = game_test::LeaderboardEntry {
+            score: self.game.score(),
+            bosses_defeated: boss_rush.bosses_defeated(),
+            time: boss_rush.time().as_secs_f32(),
+        };
+        self.leaderboard_place = self.leaderboard.add(self.game.setup().difficulty, entry);
+
+        let data_dir = ggez::filesystem::user_data_dir(ctx);
+
+        if let Err(error) = self.leaderboard.save(data_dir) {
+            eprintln!("Failed to save the boss rush leaderboard: {}", error);
+        }
     }
 
     fn update_frozen(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
@@ -232,33 +735,421 @@ impl MainState {
             _ => unreachable!(),
         }
 
+        // There is no retrying an online game, since the other player would have to agree to it.
+        if self.netplay.is_some() {
+            self.update_netplay(ctx);
+            return Ok(());
+        }
+
         let keys = keyboard::pressed_keys(ctx);
 
-        // Retry when ‘r’ is pressed.
+        // Rewinding takes a paused run back to before it was paused. Runs that are over stay over,
+        // since how they ended has already been recorded.
+        let is_paused = matches!(
+            self.state,
+            State::Frozen {
+                state: FrozenState::Paused,
+                ..
+            }
+        );
+
+        if keys.contains(&REWIND_KEY)
+            && is_paused
+            && self.can_use_practice_tools()
+            && !self.rewind_buffer.is_empty()
+        {
+            self.state = State::Playing;
+            self.rewind();
+            return Ok(());
+        }
+
+        // Retry when ‘r’ is pressed, or go back to the menu when ‘m’ is.
         if keys.contains(&keyboard::KeyCode::R) {
-            *self = Self::new(ctx);
+            self.restart(self.game.setup());
+        } else if keys.contains(&keyboard::KeyCode::M) {
+            self.open_menu();
         }
 
         Ok(())
     }
 
+    /// Starts a new run, leaving the debug overlay as it was.
+    fn restart(&mut self, setup: game_test::RunSetup) {
+        self.game = game_test::Game::new(rand::random(), setup, game_test::PLAYFIELD);
+        self.state = State::Playing;
+        self.leaderboard_place = None;
+        self.pressed_actions = Default::default();
+        self.recording = game_test::Replay::new(&self.game);
+        self.recording_start = 0;
+        self.rewind_buffer.clear();
+        self.has_gone_back = false;
+    }
+
+    /// Goes to the menu, with the current run’s setup chosen.
+    fn open_menu(&mut self) {
+        let starts = game_test::PracticeStart::all(self.game.stages());
+
+        self.state = State::Menu(Menu::new(self.game.setup(), &starts));
+    }
+
     fn draw_playing(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
         use game_test::{HealthBar, ImageDrawable};
 
         graphics::clear(ctx, SKY_COLOR.into());
 
-        for laser in &self.lasers {
-            laser.draw(ctx)?;
+        for telegraph in self.game.telegraphs() {
+            telegraph.draw(ctx)?;
+        }
+        for player in self.game.players() {
+            for laser in player.lasers() {
+                laser.draw(ctx)?;
+            }
+        }
+        for player in self
+            .game
+            .players()
+            .iter()
+            .filter(|player| player.is_playing())
+        {
+            player.ship().draw(ctx)?;
         }
-        self.ship.draw(ctx)?;
 
-        for bullet in &self.bullets {
+        for bullet in self.game.bullets() {
             bullet.draw(ctx)?;
         }
-        self.sky_core.draw(ctx)?;
+        for enemy in self.game.enemies() {
+            enemy.draw(ctx)?;
+        }
+        for item in self.game.items() {
+            item.draw(ctx)?;
+        }
+
+        for player in self
+            .game
+            .players()
+            .iter()
+            .filter(|player| player.is_playing())
+        {
+            player.ship().health_bar(ctx)?.draw(ctx)?;
+        }
+
+        self.draw_stage_text(ctx)?;
+        self.draw_boss_attack(ctx)?;
+        self.draw_results_card(ctx)?;
+
+        // Survival shows how long the players have lasted alongside the difficulty, and boss rushes
+        // how many bosses are down.
+        let run_name = match self.game.survival() {
+            Some(survival) => {
+                let seconds = survival.time().as_secs();
+
+                format!(
+                    "{} survival {}:{:02}",
+                    self.game.setup().difficulty.name(),
+                    seconds / 60,
+                    seconds % 60
+                )
+            }
+            None => match self.game.boss_rush() {
+                Some(boss_rush) => format!(
+                    "{} boss rush {}/{}",
+                    self.game.setup().difficulty.name(),
+                    boss_rush.bosses_defeated(),
+                    boss_rush.boss_count()
+                ),
+                None => self.game.setup().difficulty.name().to_string(),
+            },
+        };
+
+        for index in 0..self.game.players().len() {
+            self.draw_player_hud(index, &run_name, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Shows a player’s score and how their ship is doing, in the top right for the first player
+    /// and the top left for the second. The run’s name goes above the first player’s.
+    fn draw_player_hud(
+        &self,
+        index: usize,
+        run_name: &str,
+        ctx: &mut ggez::Context,
+    ) -> ggez::GameResult {
+        let player = &self.game.players()[index];
+        let ship = player.ship();
+        let screen_width = graphics::screen_coordinates(ctx).w;
+        let x = |width: f32| {
+            if index == 0 {
+                screen_width - width - SCORE_MARGIN
+            } else {
+                SCORE_MARGIN
+            }
+        };
+
+        let score = graphics::Text::new(
+            graphics::TextFragment::new(player.score().to_string())
+                .scale(graphics::Scale::uniform(SCORE_FONT_SIZE)),
+        );
+        let score_width = score.width(ctx) as f32;
+
+        graphics::draw(
+            ctx,
+            &score,
+            (game_test::Point::new(x(score_width), SCORE_MARGIN),),
+        )?;
+
+        let heading = match (index, self.game.players().len()) {
+            (0, 1) => run_name.to_string(),
+            (0, _) => format!("{}\nP1", run_name),
+            _ => format!("P{}", index + 1),
+        };
+        let status = if player.is_playing() {
+            format!(
+                "{} ({:?} to switch)\nPower {}/{}\nLives {} ({}/{})\nBombs {} ({:?} to use)",
+                ship.shot_type().name(),
+                self.controls(index).switch_shot,
+                ship.power_level(),
+                game_test::ShotType::MAX_POWER_LEVEL,
+                ship.lives(),
+                ship.life_pieces(),
+                game_test::Ship::LIFE_PIECES_PER_LIFE,
+                ship.bombs(),
+                self.controls(index).bomb,
+            )
+        } else {
+            "Out".to_string()
+        };
+
+        let shot = graphics::Text::new(
+            graphics::TextFragment::new(format!(
+                "{}\n{}\nGrazes {}",
+                heading,
+                status,
+                player.grazes()
+            ))
+            .scale(graphics::Scale::uniform(SHOT_FONT_SIZE)),
+        );
+        let shot_width = shot.width(ctx) as f32;
+
+        graphics::draw(
+            ctx,
+            &shot,
+            (game_test::Point::new(
+                x(shot_width),
+                SCORE_MARGIN * 2.0 + SCORE_FONT_SIZE,
+            ),),
+        )
+    }
+
+    /// Shows the name of the boss attack being faced, how long is left of it and how often it has
+    /// been captured before.
+    fn draw_boss_attack(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let attempt = match self.game.enemies().iter().find_map(|enemy| enemy.attempt()) {
+            Some(attempt) => attempt,
+            None => return Ok(()),
+        };
+
+        let attack = attempt.attack();
+        let record = self
+            .capture_history
+            .get(self.game.setup().difficulty, attack);
+
+        let text = graphics::Text::new(
+            graphics::TextFragment::new(format!(
+                "{}  {:.1}\nCaptured {}/{}",
+                attack.name,
+                attempt.time_left().as_secs_f32(),
+                record.captures,
+                record.attempts,
+            ))
+            .scale(graphics::Scale::uniform(BOSS_ATTACK_FONT_SIZE)),
+        );
+        let text_width = text.width(ctx) as f32;
+        let screen_width = graphics::screen_coordinates(ctx).w;
+
+        graphics::draw(
+            ctx,
+            &text,
+            (game_test::Point::new(
+                screen_width / 2.0 - text_width / 2.0,
+                SCORE_MARGIN,
+            ),),
+        )
+    }
+
+    /// Shows how the last boss fight of a boss rush went, in the break before the next one.
+    fn draw_results_card(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let result = match self
+            .game
+            .boss_rush()
+            .and_then(|boss_rush| boss_rush.last_result())
+        {
+            Some(result) => result,
+            None => return Ok(()),
+        };
+
+        let resources: Vec<_> = self
+            .game
+            .players()
+            .iter()
+            .map(|player| {
+                format!(
+                    "Lives {}  Bombs {}",
+                    player.ship().lives(),
+                    player.ship().bombs()
+                )
+            })
+            .collect();
+
+        let outcome = if result.has_escaped {
+            "timed out"
+        } else {
+            "defeated!"
+        };
+
+        let text = graphics::Text::new(
+            graphics::TextFragment::new(format!(
+                "{} {}\nTime {:.1}s\nCaptured {}/{}\n{}\nScore {}",
+                result.boss.name(),
+                outcome,
+                result.time.as_secs_f32(),
+                result.captures,
+                result.attacks,
+                resources.join("\n"),
+                self.game.score(),
+            ))
+            .scale(graphics::Scale::uniform(RESULTS_FONT_SIZE)),
+        );
+        let screen_dimens = graphics::screen_coordinates(ctx);
+        let (text_width, text_height) = text.dimensions(ctx);
+
+        graphics::draw(
+            ctx,
+            &text,
+            (game_test::Point::new(
+                screen_dimens.w / 2.0 - text_width as f32 / 2.0,
+                screen_dimens.h / 3.0 - text_height as f32 / 2.0,
+            ),),
+        )
+    }
+
+    /// Shows the best boss rushes on the run’s difficulty, picking out where this one placed.
+    fn draw_leaderboard(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let screen_dimens = graphics::screen_coordinates(ctx);
+        let mut lines = vec![format!(
+            "Boss rush leaderboard ({})",
+            self.game.setup().difficulty.name()
+        )];
+
+        for (i, entry) in self
+            .leaderboard
+            .entries(self.game.setup().difficulty)
+            .iter()
+            .enumerate()
+        {
+            let line = format!(
+                "{}. {}  {} bosses  {:.1}s",
+                i + 1,
+                entry.score,
+                entry.bosses_defeated,
+                entry.time
+            );
+
+            lines.push(if self.leaderboard_place == Some(i) {
+                format!("> {} <", line)
+            } else {
+                line
+            });
+        }
+
+        let text = graphics::Text::new(
+            graphics::TextFragment::new(lines.join("\n"))
+                .scale(graphics::Scale::uniform(LEADERBOARD_FONT_SIZE)),
+        );
+        let text_width = text.width(ctx) as f32;
+
+        graphics::draw(
+            ctx,
+            &text,
+            (game_test::Point::new(
+                screen_dimens.w / 2.0 - text_width / 2.0,
+                screen_dimens.h * 2.0 / 3.0,
+            ),),
+        )
+    }
+
+    fn draw_stage_text(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let screen_dimens = graphics::screen_coordinates(ctx);
+
+        // Announce stages as they start and finish, or the start of survival or a boss rush.
+        let banner = if let Some(survival) = self.game.survival() {
+            let time = survival.time();
+
+            if time < STAGE_TITLE_DURATION {
+                Some("Survival".to_string())
+            } else {
+                None
+            }
+        } else if let Some(boss_rush) = self.game.boss_rush() {
+            if boss_rush.time() < STAGE_TITLE_DURATION {
+                Some("Boss Rush".to_string())
+            } else {
+                None
+            }
+        } else if self.game.is_between_stages() {
+            Some(format!("{} clear!", self.game.stage().name()))
+        } else if self.game.stage().time() < STAGE_TITLE_DURATION {
+            Some(self.game.stage().name().to_string())
+        } else {
+            None
+        };
+
+        if let Some(banner) = banner {
+            let text = graphics::Text::new(
+                graphics::TextFragment::new(banner)
+                    .scale(graphics::Scale::uniform(BANNER_FONT_SIZE)),
+            );
+            let (text_width, text_height) = text.dimensions(ctx);
+
+            graphics::draw(
+                ctx,
+                &text,
+                (game_test::Point::new(
+                    screen_dimens.w / 2.0 - text_width as f32 / 2.0,
+                    screen_dimens.h / 3.0 - text_height as f32 / 2.0,
+                ),),
+            )?;
+        }
 
-        self.ship.health_bar(ctx)?.draw(ctx)?;
-        self.sky_core.health_bar(ctx)?.draw(ctx)?;
+        if let Some((speaker, line)) = self.game.stage().dialogue() {
+            let dialogue_box = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect {
+                    x: 0.0,
+                    y: screen_dimens.h - DIALOGUE_BOX_HEIGHT,
+                    w: screen_dimens.w,
+                    h: DIALOGUE_BOX_HEIGHT,
+                },
+                (0.0, 0.0, 0.0, DIALOGUE_BOX_ALPHA).into(),
+            )?;
+            graphics::draw(ctx, &dialogue_box, (game_test::Point::new(0.0, 0.0),))?;
+
+            let text = graphics::Text::new(
+                graphics::TextFragment::new(format!("{}: {}", speaker, line))
+                    .scale(graphics::Scale::uniform(DIALOGUE_FONT_SIZE)),
+            );
+
+            graphics::draw(
+                ctx,
+                &text,
+                (game_test::Point::new(
+                    DIALOGUE_MARGIN,
+                    screen_dimens.h - DIALOGUE_BOX_HEIGHT + DIALOGUE_MARGIN,
+                ),),
+            )?;
+        }
 
         Ok(())
     }
@@ -313,32 +1204,252 @@ impl MainState {
             ),),
         )?;
 
+        // Finished boss rushes show how they stack up against the others.
+        if self.game.boss_rush().is_some() && !matches!(state, FrozenState::Paused) {
+            self.draw_leaderboard(ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_menu(&mut self, menu: Menu, ctx: &mut ggez::Context) -> ggez::GameResult {
+        graphics::clear(ctx, SKY_COLOR.into());
+
+        let screen_dimens = graphics::screen_coordinates(ctx);
+
+        let title = graphics::Text::new(
+            graphics::TextFragment::new("Set up a run")
+                .scale(graphics::Scale::uniform(MENU_TITLE_FONT_SIZE)),
+        );
+        let title_width = title.width(ctx) as f32;
+
+        graphics::draw(
+            ctx,
+            &title,
+            (game_test::Point::new(
+                screen_dimens.w / 2.0 - title_width / 2.0,
+                screen_dimens.h / 4.0,
+            ),),
+        )?;
+
+        let starts = game_test::PracticeStart::all(self.game.stages());
+
+        for (i, &row) in menu.rows().iter().enumerate() {
+            let choice = match row {
+                MenuRow::Difficulty => format!("Difficulty: {}", menu.difficulty.name()),
+                MenuRow::Players if menu.is_co_op => "Players: 2".to_string(),
+                MenuRow::Players => "Players: 1".to_string(),
+                MenuRow::Start => match menu.start {
+                    MenuStart::FullGame => "Full game".to_string(),
+                    MenuStart::Survival => "Survival".to_string(),
+                    MenuStart::BossRush => "Boss rush".to_string(),
+                    MenuStart::Practice(index) => {
+                        format!("Practice: {}", starts[index].name(self.game.stages()))
+                    }
+                },
+                MenuRow::Lives => format!("Lives: {}", menu.lives),
+                MenuRow::Bombs => format!("Bombs: {}", menu.bombs),
+                MenuRow::Power => format!("Power: {}", menu.power_level),
+            };
+            let label = if row == menu.row {
+                format!("< {} >", choice)
+            } else {
+                choice
+            };
+            let text = graphics::Text::new(
+                graphics::TextFragment::new(label).scale(graphics::Scale::uniform(MENU_FONT_SIZE)),
+            );
+            let text_width = text.width(ctx) as f32;
+
+            graphics::draw(
+                ctx,
+                &text,
+                (game_test::Point::new(
+                    screen_dimens.w / 2.0 - text_width / 2.0,
+                    screen_dimens.h / 2.0 + MENU_FONT_SIZE * 1.5 * i as f32,
+                ),),
+            )?;
+        }
+
         Ok(())
     }
+
+    fn draw_debug_overlay(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        use game_test::Hitbox;
+
+        if !self.debug_overlay.is_visible() {
+            return Ok(());
+        }
+
+        let lasers = self
+            .game
+            .players()
+            .iter()
+            .flat_map(|player| player.lasers());
+
+        let hitboxes: Vec<_> = self
+            .game
+            .players()
+            .iter()
+            .filter(|player| player.is_playing())
+            .map(|player| player.ship().hitbox())
+            .chain(self.game.enemies().iter().map(|enemy| enemy.hitbox()))
+            .chain(lasers.clone().map(Hitbox::hitbox))
+            .chain(self.game.bullets().iter().map(Hitbox::hitbox))
+            .collect();
+
+        let stats = game_test::DebugStats {
+            bullets: self.game.bullets().len(),
+            lasers: lasers.count(),
+            enemies: self.game.enemies().len(),
+            seed: self.game.seed(),
+            boss_phase: self
+                .game
+                .enemies()
+                .iter()
+                .find_map(|enemy| enemy.boss_phase()),
+            rank: self.game.rank().value(),
+        };
+
+        self.debug_overlay.draw(ctx, &hitboxes, &stats)
+    }
 }
 
 impl event::EventHandler for MainState {
     fn update(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
-        // Allow the user to retry if the game is in a frozen state.
-        match self.state {
-            State::Playing => self.update_playing(ctx),
-            State::Frozen { .. } => self.update_frozen(ctx),
+        while ggez::timer::check_update_time(ctx, game_test::TICKS_PER_SECOND) {
+            // We still drain the elapsed ticks while the debug overlay has paused the simulation,
+            // so that unpausing doesn’t make the game race to catch up.
+            if !self.debug_overlay.should_tick() {
+                continue;
+            }
+
+            let tick_start = Instant::now();
+
+            // Allow the user to retry if the game is in a frozen state.
+            match self.state {
+                // The menu only reacts to key presses.
+                State::Menu(_) => (),
+                State::Playing if self.netplay.is_some() => self.update_netplay(ctx),
+                State::Playing => self.update_playing(ctx)?,
+                State::Frozen { .. } => self.update_frozen(ctx)?,
+            }
+
+            self.debug_overlay.record_tick_time(tick_start.elapsed());
         }
+
+        Ok(())
     }
 
     fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
         match self.state {
+            State::Menu(menu) => self.draw_menu(menu, ctx)?,
             State::Playing => self.draw_playing(ctx)?,
             State::Frozen { .. } => self.draw_frozen(ctx)?,
         }
 
+        self.draw_debug_overlay(ctx)?;
+
         graphics::present(ctx)
     }
+
+    fn quit_event(&mut self, ctx: &mut ggez::Context) -> bool {
+        self.suspend(ctx);
+        false
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut ggez::Context,
+        keycode: event::KeyCode,
+        _keymods: event::KeyMods,
+        repeat: bool,
+    ) {
+        if repeat {
+            return;
+        }
+
+        let can_quick_save = matches!(self.state, State::Playing) && self.can_use_practice_tools();
+
+        match keycode {
+            // Keep ggez’s default behaviour of quitting on escape.
+            event::KeyCode::Escape => {
+                self.suspend(ctx);
+                event::quit(ctx);
+            }
+            event::KeyCode::F3 => self.debug_overlay.toggle_visible(),
+            event::KeyCode::F4 => self.debug_overlay.toggle_paused(),
+            event::KeyCode::F5 => self.debug_overlay.request_step(),
+            event::KeyCode::F6 if can_quick_save => self.quick_save(ctx),
+            event::KeyCode::F7 if can_quick_save => self.quick_load(ctx),
+            // Pick a row on the menu with W and S, change it with A and D, and start with space or
+            // enter.
+            event::KeyCode::W | event::KeyCode::Up => {
+                if let State::Menu(ref mut menu) = self.state {
+                    menu.select_next_row(false);
+                }
+            }
+            event::KeyCode::S | event::KeyCode::Down => {
+                if let State::Menu(ref mut menu) = self.state {
+                    menu.select_next_row(true);
+                }
+            }
+            event::KeyCode::A | event::KeyCode::Left => {
+                let start_count = game_test::PracticeStart::all(self.game.stages()).len();
+
+                if let State::Menu(ref mut menu) = self.state {
+                    menu.change_choice(false, start_count);
+                }
+            }
+            event::KeyCode::D | event::KeyCode::Right => {
+                let start_count = game_test::PracticeStart::all(self.game.stages()).len();
+
+                if let State::Menu(ref mut menu) = self.state {
+                    menu.change_choice(true, start_count);
+                }
+            }
+            event::KeyCode::Space | event::KeyCode::Return => {
+                if let State::Menu(menu) = self.state {
+                    let starts = game_test::PracticeStart::all(self.game.stages());
+
+                    self.restart(menu.setup(&starts));
+                }
+            }
+            // Practice runs can be retried at any time, to have another go at the same part.
+            event::KeyCode::R => {
+                if let (State::Playing, game_test::RunMode::Practice(_)) =
+                    (self.state, self.game.setup().mode)
+                {
+                    self.restart(self.game.setup());
+                }
+            }
+            _ => (),
+        }
+
+        // Each player switches shot types and uses bombs with their own keys, on the next tick.
+        if let State::Playing = self.state {
+            for index in 0..self.pressed_actions.len() {
+                let controls = self.controls(index);
+                let pressed = &mut self.pressed_actions[index];
+
+                pressed.switch_shot |= keycode == controls.switch_shot;
+                pressed.bomb |= keycode == controls.bomb;
+            }
+        }
+    }
 }
 
 fn main() -> ggez::GameResult {
     use ggez::conf;
 
+    let netplay_options = match NetplayOptions::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n{}", error, NETPLAY_USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let window_setup: conf::WindowSetup = Default::default();
     let window_setup = window_setup.title("Bullet Hell");
 
@@ -346,6 +1457,15 @@ fn main() -> ggez::GameResult {
         .window_setup(window_setup)
         .build()?;
 
-    let state = &mut MainState::new(&mut ctx);
+    let state = &mut match netplay_options {
+        Some(options) => match MainState::with_netplay(options, &mut ctx) {
+            Ok(state) => state,
+            Err(error) => {
+                eprintln!("Failed to start the online game: {}", error);
+                std::process::exit(1);
+            }
+        },
+        None => MainState::new(&mut ctx),
+    };
     event::run(&mut ctx, &mut event_loop, state)
 }
@@ -0,0 +1,223 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// Curves that reshape linear progress through a movement, so that things can speed up or slow
+/// down gracefully instead of starting and stopping dead.
+#[derive(Clone, Copy, Default, Deserialize, Serialize)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+}
+
+impl Easing {
+    /// Maps progress `t` from 0 to 1 onto eased progress from 0 to 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = num::clamp(t, 0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::SineInOut => -((std::f32::consts::PI * t).cos() - 1.0) / 2.0,
+        }
+    }
+}
+
+/// A curve made from points relative to wherever the path is started from.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Path {
+    /// A Bézier curve that starts at the first point and ends at the last, pulled towards the
+    /// points in between.
+    Bezier(Vec<crate::Vector>),
+    /// A Catmull-Rom spline that passes through every point.
+    CatmullRom(Vec<crate::Vector>),
+}
+
+impl Path {
+    /// The offset from the start of the path after progress `t` from 0 to 1.
+    pub fn offset_at(&self, t: f32) -> crate::Vector {
+        let t = num::clamp(t, 0.0, 1.0);
+
+        match self {
+            Path::Bezier(points) => {
+                // De Casteljau’s algorithm: repeatedly interpolate between neighbouring points
+                // until only one is left.
+                let mut points = points.clone();
+
+                for len in (1..points.len()).rev() {
+                    for i in 0..len {
+                        let step = (points[i + 1] - points[i]) * t;
+                        points[i] += step;
+                    }
+                }
+
+                points.first().copied().unwrap_or_else(crate::Vector::zeros)
+            }
+            Path::CatmullRom(points) => {
+                let segments = match points.len() {
+                    0 => return crate::Vector::zeros(),
+                    1 => return points[0],
+                    len => len - 1,
+                };
+
+                let scaled = t * segments as f32;
+                let i = (scaled as usize).min(segments - 1);
+                let t = scaled - i as f32;
+
+                // The ends of the spline have no neighbours beyond them, so we repeat the end
+                // points in their place.
+                let p0 = points[i.saturating_sub(1)];
+                let p1 = points[i];
+                let p2 = points[i + 1];
+                let p3 = points[(i + 2).min(segments)];
+
+                (p1 * 2.0
+                    + (p2 - p0) * t
+                    + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (t * t)
+                    + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (t * t * t))
+                    * 0.5
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Movement {
+    /// Moves at a constant velocity, in pixels per second, forever.
+    Linear { velocity: crate::Vector },
+    /// Follows a path from wherever the movement starts.
+    FollowPath {
+        path: Path,
+        duration: Duration,
+        easing: Easing,
+    },
+    /// Moves to a point on the screen.
+    MoveTo {
+        target: crate::Point,
+        duration: Duration,
+        easing: Easing,
+    },
+    /// Swings back and forth around wherever the movement starts, either forever or for a while.
+    Hover {
+        amplitude: crate::Vector,
+        period: Duration,
+        duration: Option<Duration>,
+    },
+    /// Stays put.
+    Wait { duration: Duration },
+}
+
+/// A sequence of movements, carried out one after another.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MovementScript {
+    steps: Vec<Movement>,
+    current_step: usize,
+    elapsed: Duration,
+    /// Where the current step started from.
+    origin: Option<crate::Point>,
+}
+
+impl MovementScript {
+    pub fn new(steps: Vec<Movement>) -> Self {
+        Self {
+            steps,
+            current_step: 0,
+            elapsed: Duration::from_millis(0),
+            origin: None,
+        }
+    }
+
+    pub fn steps_completed(&self) -> usize {
+        self.current_step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current_step >= self.steps.len()
+    }
+
+    /// Moves `pos` along the script by a tick.
+    pub fn update(&mut self, pos: &mut crate::Point, delta_time: Duration) {
+        let movement = match self.steps.get(self.current_step) {
+            Some(movement) => movement,
+            None => return,
+        };
+
+        let origin = *self.origin.get_or_insert(*pos);
+        self.elapsed += delta_time;
+
+        let progress = |duration: Duration| {
+            if duration == Duration::from_millis(0) {
+                1.0
+            } else {
+                (self.elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+            }
+        };
+
+        let is_step_finished = match movement {
+            Movement::Linear { velocity } => {
+                *pos += velocity * delta_time.as_secs_f32();
+                false
+            }
+            Movement::FollowPath {
+                path,
+                duration,
+                easing,
+            } => {
+                let t = progress(*duration);
+                *pos = origin + path.offset_at(easing.apply(t));
+                t >= 1.0
+            }
+            Movement::MoveTo {
+                target,
+                duration,
+                easing,
+            } => {
+                let t = progress(*duration);
+                *pos = origin + (target - origin) * easing.apply(t);
+                t >= 1.0
+            }
+            Movement::Hover {
+                amplitude,
+                period,
+                duration,
+            } => {
+                let angle =
+                    self.elapsed.as_secs_f32() / period.as_secs_f32() * std::f32::consts::PI * 2.0;
+                *pos = origin + amplitude * angle.sin();
+                duration.is_some_and(|duration| self.elapsed >= duration)
+            }
+            Movement::Wait { duration } => self.elapsed >= *duration,
+        };
+
+        if is_step_finished {
+            self.current_step += 1;
+            self.elapsed = Duration::from_millis(0);
+            self.origin = None;
+        }
+    }
+}
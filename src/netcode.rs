@@ -0,0 +1,440 @@
+use {
+    crate::PlayerInput,
+    std::{
+        collections::VecDeque,
+        io,
+        net::{SocketAddr, ToSocketAddrs, UdpSocket},
+        time::{Duration, Instant},
+    },
+};
+
+/// How many ticks past the last of the peer’s inputs to have arrived the game may run, guessing
+/// what the peer is doing, before it waits for them to catch up.
+pub const MAX_PREDICTION: u32 = 8;
+/// The most inputs sent in one packet. Packets repeat every input the peer hasn’t heard yet, so
+/// that losing a packet doesn’t lose any inputs.
+const MAX_INPUTS_PER_PACKET: usize = 64;
+const PACKET_HEADER_SIZE: usize = 9;
+const MAX_PACKET_SIZE: usize = PACKET_HEADER_SIZE + MAX_INPUTS_PER_PACKET;
+
+/// Something that carries packets to and from the peer. Packets may be lost, or arrive late or
+/// out of order.
+pub trait Transport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+
+    /// Every packet that has arrived since this was last asked.
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>>;
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        T::send(self, packet)
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        T::receive(self)
+    }
+}
+
+/// Carries packets to and from the peer over UDP.
+pub struct UdpTransport {
+    socket: UdpSocket,
+    peer: SocketAddr,
+}
+
+impl UdpTransport {
+    pub fn bind(local: impl ToSocketAddrs, peer: impl ToSocketAddrs) -> anyhow::Result<Self> {
+        let socket = UdpSocket::bind(local)?;
+        socket.set_nonblocking(true)?;
+
+        let peer = peer
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("the peer’s address didn’t resolve to anything"))?;
+
+        Ok(Self { socket, peer })
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.socket.send_to(packet, self.peer).map(|_| ())
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let mut packets = vec![];
+        let mut buffer = [0; MAX_PACKET_SIZE];
+
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                // Anything that isn’t from the peer is ignored.
+                Ok((len, from)) if from == self.peer => packets.push(buffer[..len].to_vec()),
+                Ok(_) => (),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(packets),
+                // Some platforms report packets that couldn’t be delivered the next time the
+                // socket is read, which just means that the peer hasn’t started yet.
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => (),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+/// Makes a transport worse than it really is, holding packets back and losing some of them at
+/// random, for trying out netplay between two games on the same machine.
+pub struct NetworkShim<T> {
+    transport: T,
+    /// How long sent packets are held back for.
+    latency: Duration,
+    /// The chance of a sent packet being lost.
+    loss: f64,
+    rng: crate::GameRng,
+    held_back: VecDeque<(Instant, Vec<u8>)>,
+}
+
+impl<T: Transport> NetworkShim<T> {
+    pub fn new(transport: T, latency: Duration, loss: f64) -> Self {
+        use rand::SeedableRng;
+
+        Self {
+            transport,
+            latency,
+            loss,
+            rng: crate::GameRng::from_entropy(),
+            held_back: VecDeque::new(),
+        }
+    }
+
+    /// Sends on every packet that has been held back for long enough.
+    fn send_due(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+
+        while let Some((due, _)) = self.held_back.front() {
+            if *due > now {
+                break;
+            }
+
+            let (_, packet) = self.held_back.pop_front().unwrap();
+            self.transport.send(&packet)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Transport> Transport for NetworkShim<T> {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        use rand::Rng;
+
+        if !self.rng.gen_bool(self.loss) {
+            self.held_back
+                .push_back((Instant::now() + self.latency, packet.to_vec()));
+        }
+
+        self.send_due()
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        self.send_due()?;
+        self.transport.receive()
+    }
+}
+
+/// What goes in each packet: the sender’s inputs from `first_tick` on, and how many of the
+/// receiver’s inputs the sender has heard.
+struct InputPacket {
+    first_tick: u32,
+    received: u32,
+    inputs: Vec<PlayerInput>,
+}
+
+impl InputPacket {
+    fn encode(&self) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(PACKET_HEADER_SIZE + self.inputs.len());
+
+        packet.extend_from_slice(&self.first_tick.to_le_bytes());
+        packet.extend_from_slice(&self.received.to_le_bytes());
+        packet.push(self.inputs.len() as u8);
+        packet.extend(self.inputs.iter().map(|input| input.to_bits()));
+
+        packet
+    }
+
+    /// Reads a packet, or gives back `None` if it is garbled.
+    fn decode(packet: &[u8]) -> Option<Self> {
+        use std::convert::TryInto;
+
+        let header = packet.get(..PACKET_HEADER_SIZE)?;
+        let inputs = &packet[PACKET_HEADER_SIZE..];
+
+        if inputs.len() != usize::from(header[8]) {
+            return None;
+        }
+
+        Some(Self {
+            first_tick: u32::from_le_bytes(header[0..4].try_into().ok()?),
+            received: u32::from_le_bytes(header[4..8].try_into().ok()?),
+            inputs: inputs.iter().copied().map(PlayerInput::from_bits).collect(),
+        })
+    }
+}
+
+/// Keeps two games in step over the network using rollback. Each game runs on its own player’s
+/// inputs straight away, guessing that the peer is still doing whatever they were last heard
+/// doing. When the peer’s real inputs arrive and turn out to be different from the guess, the
+/// game rolls back to the first tick that was guessed wrong and plays the ticks since again.
+///
+/// Local inputs are held back by a few ticks before they take effect, which gives them time to
+/// reach the peer and makes rolling back less likely.
+pub struct RollbackSession<T> {
+    transport: T,
+    local_player: usize,
+    /// The next tick to be played.
+    tick: u32,
+    /// Every local input, by tick.
+    local_inputs: Vec<PlayerInput>,
+    /// Every input heard from the peer, by tick. Inputs are only kept once every input before
+    /// them has arrived too.
+    remote_inputs: Vec<PlayerInput>,
+    /// How many local inputs the peer has heard.
+    peer_received: u32,
+    /// The guesses made for the peer’s inputs on ticks that have been played but not heard
+    /// from the peer yet, starting from the tick after the last one heard.
+    predictions: VecDeque<PlayerInput>,
+    /// The first tick whose guess turned out wrong, if the game needs rolling back.
+    rollback_tick: Option<u32>,
+}
+
+impl<T: Transport> RollbackSession<T> {
+    /// Starts a session for two players, where this game is played by `local_player` (0 or 1).
+    /// Both games need to use the same input delay.
+    pub fn new(transport: T, local_player: usize, input_delay: u32) -> Self {
+        // Nobody does anything during the input delay at the start of the game.
+        let delay_inputs = vec![PlayerInput::default(); input_delay as usize];
+
+        Self {
+            transport,
+            local_player,
+            tick: 0,
+            local_inputs: delay_inputs.clone(),
+            remote_inputs: delay_inputs,
+            peer_received: 0,
+            predictions: VecDeque::new(),
+            rollback_tick: None,
+        }
+    }
+
+    pub fn local_player(&self) -> usize {
+        self.local_player
+    }
+
+    /// The next tick to be played.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Reads every packet from the peer, noting the first tick that needs playing again if any
+    /// of the peer’s inputs were guessed wrong.
+    pub fn poll(&mut self) -> io::Result<()> {
+        for packet in self.transport.receive()? {
+            let packet = match InputPacket::decode(&packet) {
+                Some(packet) => packet,
+                None => continue,
+            };
+
+            // The peer can’t have heard more inputs than have been sent, so a packet that says
+            // otherwise is garbled.
+            if packet.received > self.local_inputs.len() as u32 {
+                continue;
+            }
+
+            self.peer_received = self.peer_received.max(packet.received);
+
+            // Skip over the inputs that have already been heard. Packets are sent starting from
+            // the first input the peer knows we haven’t heard, so there are never gaps.
+            let heard = self.remote_inputs.len() as u32;
+
+            if packet.first_tick > heard {
+                continue;
+            }
+
+            let new_inputs = packet
+                .inputs
+                .into_iter()
+                .skip((heard - packet.first_tick) as usize);
+
+            for input in new_inputs {
+                let tick = self.remote_inputs.len() as u32;
+
+                if let Some(prediction) = self.predictions.pop_front() {
+                    if prediction != input {
+                        self.rollback_tick = Some(self.rollback_tick.map_or(tick, |t| t.min(tick)));
+                    }
+                }
+
+                self.remote_inputs.push(input);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The first tick that needs playing again because the peer’s inputs were guessed wrong, if
+    /// any. The game should go back to how it was at the start of that tick, then play every
+    /// tick up to [`tick`](Self::tick) again using [`inputs`](Self::inputs).
+    pub fn take_rollback(&mut self) -> Option<u32> {
+        self.rollback_tick.take()
+    }
+
+    /// Whether the game can play another tick without getting too far ahead of the peer.
+    pub fn can_advance(&self) -> bool {
+        self.tick < self.remote_inputs.len() as u32 + MAX_PREDICTION
+    }
+
+    /// Whether every input for the ticks played so far has been heard from the peer.
+    pub fn is_confirmed(&self) -> bool {
+        self.tick <= self.remote_inputs.len() as u32
+    }
+
+    /// Adds the local player’s input, which takes effect after the input delay.
+    pub fn add_local_input(&mut self, input: PlayerInput) {
+        self.local_inputs.push(input);
+    }
+
+    /// Sends the peer every input they haven’t heard yet, along with how many of theirs have been
+    /// heard. This needs doing every frame, even while waiting for the peer, since otherwise
+    /// losing the last packets each way would leave both games waiting for each other forever.
+    pub fn send_pending(&mut self) -> io::Result<()> {
+        let first_tick = self.peer_received;
+        let inputs = self.local_inputs[first_tick as usize..]
+            .iter()
+            .copied()
+            .take(MAX_INPUTS_PER_PACKET)
+            .collect();
+
+        let packet = InputPacket {
+            first_tick,
+            received: self.remote_inputs.len() as u32,
+            inputs,
+        };
+
+        self.transport.send(&packet.encode())
+    }
+
+    /// Both players’ inputs for a tick that is being played, guessing the peer’s if they
+    /// haven’t been heard yet.
+    pub fn inputs(&mut self, tick: u32) -> [PlayerInput; 2] {
+        let local_input = self.local_inputs[tick as usize];
+        let heard = self.remote_inputs.len() as u32;
+
+        let remote_input = if tick < heard {
+            self.remote_inputs[tick as usize]
+        } else {
+            let prediction = self.remote_inputs.last().copied().unwrap_or_default();
+            let index = (tick - heard) as usize;
+
+            // Replaying a tick makes a fresh guess for it.
+            self.predictions.truncate(index);
+            self.predictions.push_back(prediction);
+
+            prediction
+        };
+
+        let mut inputs = [remote_input; 2];
+        inputs[self.local_player] = local_input;
+
+        inputs
+    }
+
+    /// Moves on to the next tick, once the current one has been played.
+    pub fn advance(&mut self) {
+        self.tick += 1;
+    }
+}
+
+/// An online game: a rollback session, along with the snapshots of the game it rolls back to.
+/// The game is always played on [`PLAYFIELD`](crate::PLAYFIELD), whatever the size of
+/// either window, since both games need to play out exactly the same.
+pub struct Netplay<T> {
+    session: RollbackSession<T>,
+    /// How the game was at the start of each of the last few ticks, to roll back to.
+    snapshots: VecDeque<(u32, crate::Game)>,
+}
+
+impl<T: Transport> Netplay<T> {
+    pub fn new(session: RollbackSession<T>) -> Self {
+        Self {
+            session,
+            snapshots: VecDeque::new(),
+        }
+    }
+
+    pub fn session(&self) -> &RollbackSession<T> {
+        &self.session
+    }
+
+    /// Reads every packet from the peer. See [`RollbackSession::poll`].
+    pub fn poll(&mut self) -> io::Result<()> {
+        self.session.poll()
+    }
+
+    /// Sends the peer every input they haven’t heard yet. See
+    /// [`RollbackSession::send_pending`].
+    pub fn send_pending(&mut self) -> io::Result<()> {
+        self.session.send_pending()
+    }
+
+    /// Goes back over any ticks where the peer’s inputs were guessed wrong, playing them again
+    /// with the inputs as they are now known.
+    pub fn roll_back(&mut self, game: &mut crate::Game) -> anyhow::Result<()> {
+        let rollback_tick = match self.session.take_rollback() {
+            Some(rollback_tick) => rollback_tick,
+            None => return Ok(()),
+        };
+
+        // Ticks are never guessed further ahead than the snapshots go back.
+        let index = self
+            .snapshots
+            .iter()
+            .position(|&(tick, _)| tick == rollback_tick)
+            .ok_or_else(|| anyhow::anyhow!("there is no snapshot of tick {}", rollback_tick))?;
+        self.snapshots.truncate(index + 1);
+        *game = self.snapshots[index].1.clone();
+
+        // Boss attacks finished while playing ticks again have already been seen once, and
+        // online games stay out of the capture history anyway.
+        for tick in rollback_tick..self.session.tick() {
+            if tick != rollback_tick {
+                self.snapshots.push_back((tick, game.clone()));
+            }
+
+            let inputs = self.session.inputs(tick);
+            game.update(&inputs, crate::PLAYFIELD);
+        }
+
+        Ok(())
+    }
+
+    /// Plays the next tick with the local player’s input, if the peer isn’t too far behind.
+    /// Returns whether the local input was used.
+    pub fn advance(&mut self, game: &mut crate::Game, local_input: PlayerInput) -> bool {
+        if !self.session.can_advance() {
+            return false;
+        }
+
+        self.session.add_local_input(local_input);
+
+        let tick = self.session.tick();
+        self.snapshots.push_back((tick, game.clone()));
+
+        while self.snapshots.len() > MAX_PREDICTION as usize + 1 {
+            self.snapshots.pop_front();
+        }
+
+        let inputs = self.session.inputs(tick);
+        game.update(&inputs, crate::PLAYFIELD);
+        self.session.advance();
+
+        true
+    }
+}
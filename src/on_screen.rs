@@ -1,8 +1,8 @@
 use ggez::graphics;
 
 pub trait OnScreen: crate::Hitbox {
-    fn is_on_screen(&self, ctx: &ggez::Context) -> bool {
-        self.hitbox().overlaps(&graphics::screen_coordinates(ctx))
+    fn is_on_screen(&self, screen: graphics::Rect) -> bool {
+        self.hitbox().overlaps(&screen)
     }
 }
 
@@ -0,0 +1,168 @@
+use {
+    crate::{Health, OnScreen},
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// A flimsy fighter that flies across the screen, taking the odd potshot at the ship.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PopcornFighter {
+    pos: crate::Point,
+    movement: crate::MovementScript,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
+    health: u16,
+    max_health: u16,
+    time_since_shot: Duration,
+    has_entered_screen: bool,
+}
+
+impl PopcornFighter {
+    const IMG_DATA: &[u8] = include_bytes!("popcorn_fighter.png");
+    /// How much health the fighter has on Normal.
+    const BASE_HEALTH: u16 = 3;
+    const SPEED: f32 = 120.0;
+    const BULLET_SPEED: f32 = 1.5;
+    const SHOT_COOLDOWN: Duration = Duration::from_millis(1500);
+    /// How far apart shots are fanned out on difficulties where the fighter fires more than one.
+    const SPREAD: f32 = 0.3;
+    const BULLET_COLOR: graphics::Color = graphics::Color::new(1.0, 0.9, 0.3, 1.0);
+
+    /// Creates a fighter that flies along its entry movement (if it has one) and then carries on in
+    /// a straight line in the direction of `heading`.
+    pub fn new(
+        pos: crate::Point,
+        heading: crate::Vector,
+        entry: Option<crate::Movement>,
+        difficulty: &crate::DifficultySettings,
+    ) -> Self {
+        let movement = entry
+            .into_iter()
+            .chain(std::iter::once(crate::Movement::Linear {
+                velocity: heading * Self::SPEED,
+            }))
+            .collect();
+
+        let max_health = difficulty.max_health(Self::BASE_HEALTH);
+
+        Self {
+            pos,
+            movement: crate::MovementScript::new(movement),
+            sprite_cache: crate::SpriteCache::new(Self::IMG_DATA),
+            health: max_health,
+            max_health,
+            time_since_shot: Duration::from_millis(0),
+            has_entered_screen: false,
+        }
+    }
+}
+
+impl From<PopcornFighter> for crate::SavedEnemy {
+    fn from(enemy: PopcornFighter) -> Self {
+        Self::PopcornFighter(enemy)
+    }
+}
+
+impl crate::EnemyBehaviour for PopcornFighter {
+    const SCORE_VALUE: u32 = 100;
+
+    fn movement(&mut self, screen: graphics::Rect, delta_time: Duration) {
+        self.movement.update(&mut self.pos, delta_time);
+
+        if self.is_on_screen(screen) {
+            self.has_entered_screen = true;
+        }
+    }
+
+    fn attack(
+        &mut self,
+        _screen: graphics::Rect,
+        _rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet> {
+        self.time_since_shot += delta_time;
+
+        // Don’t shoot at the ship from off-screen, since it has no chance of seeing it coming.
+        if !self.has_entered_screen || self.time_since_shot < Self::SHOT_COOLDOWN {
+            return vec![];
+        }
+
+        self.time_since_shot = Duration::from_millis(0);
+
+        crate::Bullet::spread(
+            self.pos,
+            target,
+            difficulty.bullet_count(1),
+            Self::SPREAD,
+            Self::BULLET_SPEED,
+        )
+        .into_iter()
+        .map(|bullet| {
+            bullet
+                .with_shape(crate::BulletShape::Rice)
+                .with_color(Self::BULLET_COLOR)
+        })
+        .collect()
+    }
+
+    fn drops(&self, rng: &mut crate::GameRng) -> Vec<crate::ItemKind> {
+        use rand::Rng;
+
+        // Only the occasional fighter carries power.
+        if rng.gen_ratio(1, 4) {
+            vec![crate::ItemKind::Point, crate::ItemKind::Power]
+        } else {
+            vec![crate::ItemKind::Point]
+        }
+    }
+
+    fn has_left_screen(&self, screen: graphics::Rect) -> bool {
+        self.has_entered_screen && !self.is_on_screen(screen)
+    }
+}
+
+impl crate::Position for PopcornFighter {
+    fn pos(&self) -> crate::Point {
+        self.pos
+    }
+
+    fn pos_mut(&mut self) -> &mut crate::Point {
+        &mut self.pos
+    }
+}
+
+impl Health for PopcornFighter {
+    fn max_health(&self) -> u16 {
+        self.max_health
+    }
+
+    fn health(&self) -> u16 {
+        self.health
+    }
+
+    fn health_mut(&mut self) -> &mut u16 {
+        &mut self.health
+    }
+}
+
+impl crate::TakeDamage for PopcornFighter {}
+
+impl AsRef<crate::SpriteCache> for PopcornFighter {
+    fn as_ref(&self) -> &crate::SpriteCache {
+        &self.sprite_cache
+    }
+}
+
+impl crate::RestoreSprite for PopcornFighter {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(Self::IMG_DATA);
+    }
+}
+
+impl crate::Hitbox for PopcornFighter {
+    const WIDTH_MUL: f32 = 0.75;
+    const HEIGHT_MUL: f32 = 0.75;
+}
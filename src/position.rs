@@ -1,11 +1,9 @@
-use ggez::graphics;
-
 pub trait Position {
     fn pos(&self) -> crate::Point;
     fn pos_mut(&mut self) -> &mut crate::Point;
 }
 
-pub trait CenterPosition: AsRef<graphics::Image> + Position {
+pub trait CenterPosition: AsRef<crate::SpriteCache> + Position {
     fn center_pos(&self) -> crate::Point {
         let img = self.as_ref();
         let dimens = img.dimensions();
@@ -15,4 +13,4 @@ pub trait CenterPosition: AsRef<graphics::Image> + Position {
     }
 }
 
-impl<T: AsRef<graphics::Image> + Position> CenterPosition for T {}
+impl<T: AsRef<crate::SpriteCache> + Position> CenterPosition for T {}
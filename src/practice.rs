@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Somewhere in the game that a practice run can start from.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PracticeStart {
+    /// The start of a stage.
+    Stage(usize),
+    /// One of a boss’s phases (counting from 1), given by the stage and which of the stage’s
+    /// bosses it is (counting mid-bosses, from 0).
+    BossPhase {
+        stage: usize,
+        boss: usize,
+        phase: u16,
+    },
+}
+
+impl PracticeStart {
+    /// Every start point in the given stages, in the order they are played.
+    pub fn all(stages: &[crate::Stage]) -> Vec<Self> {
+        let mut starts = vec![];
+
+        for (stage_index, stage) in stages.iter().enumerate() {
+            starts.push(PracticeStart::Stage(stage_index));
+
+            for (boss_index, boss) in stage.bosses().into_iter().enumerate() {
+                starts.extend((1..=boss.boss_attacks().len()).map(|phase| {
+                    PracticeStart::BossPhase {
+                        stage: stage_index,
+                        boss: boss_index,
+                        phase: phase as u16,
+                    }
+                }));
+            }
+        }
+
+        starts
+    }
+
+    pub fn stage_index(self) -> usize {
+        match self {
+            PracticeStart::Stage(stage) | PracticeStart::BossPhase { stage, .. } => stage,
+        }
+    }
+
+    pub fn name(self, stages: &[crate::Stage]) -> String {
+        match self {
+            PracticeStart::Stage(stage) => stages[stage].name.clone(),
+            PracticeStart::BossPhase { stage, boss, phase } => {
+                let attack = stages[stage].bosses()[boss].boss_attacks()[usize::from(phase) - 1];
+
+                format!("{} – {}", stages[stage].name, attack.name)
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// How much rank goes up for every second the player survives at the lowest power level.
+const SURVIVAL_GAIN: f32 = 0.004;
+/// How much faster rank goes up for every power level above the lowest.
+const POWER_GAIN_MUL: f32 = 0.5;
+/// How much rank goes up for every point scored.
+const SCORE_GAIN: f32 = 0.000_002;
+const DEATH_LOSS: f32 = 0.3;
+const BOMB_LOSS: f32 = 0.1;
+/// How much faster bullets are at the highest rank, as a fraction of their usual speed.
+const MAX_SPEED_BOOST: f32 = 0.3;
+/// How many more bullets are in each volley at the highest rank, as a fraction of the usual amount.
+const MAX_DENSITY_BOOST: f32 = 0.5;
+
+/// A hidden measure of how well the player is doing, which quietly makes attacks harder for
+/// players who are doing well and eases off on players who are struggling. Rank goes from 0 to 1.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Rank {
+    value: f32,
+}
+
+impl Rank {
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Raises the rank for surviving another tick, faster the more powerful the ship is.
+    pub fn update(&mut self, delta_time: Duration, power_level: u8) {
+        let power_mul = 1.0 + POWER_GAIN_MUL * f32::from(power_level.saturating_sub(1));
+
+        self.change_by(SURVIVAL_GAIN * power_mul * delta_time.as_secs_f32());
+    }
+
+    pub fn score(&mut self, points: u32) {
+        self.change_by(SCORE_GAIN * points as f32);
+    }
+
+    pub fn die(&mut self) {
+        self.change_by(-DEATH_LOSS);
+    }
+
+    pub fn use_bomb(&mut self) {
+        self.change_by(-BOMB_LOSS);
+    }
+
+    fn change_by(&mut self, amount: f32) {
+        self.value = num::clamp(self.value + amount, 0.0, 1.0);
+    }
+
+    /// Makes the difficulty’s bullets faster and denser according to the rank.
+    pub fn apply(&self, difficulty: &crate::DifficultySettings) -> crate::DifficultySettings {
+        crate::DifficultySettings {
+            bullet_speed: difficulty.bullet_speed * (1.0 + MAX_SPEED_BOOST * self.value),
+            bullet_density: difficulty.bullet_density * (1.0 + MAX_DENSITY_BOOST * self.value),
+            ..*difficulty
+        }
+    }
+}
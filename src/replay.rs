@@ -0,0 +1,92 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::path::Path,
+};
+
+/// How many ticks apart the rank is noted down while recording.
+const RANK_INTERVAL: usize = crate::TICKS_PER_SECOND as usize;
+
+/// A whole run, kept as how it started and what every player did on every tick. Since a game
+/// always plays out the same way from the same seed, setup and inputs, this is all it takes to
+/// play the run again.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub setup: crate::RunSetup,
+    /// Every player’s input on each tick, starting from the first.
+    pub inputs: Vec<Vec<crate::PlayerInput>>,
+    /// The rank at the start of every second, so that playing the replay back can tell when it has
+    /// gone out of step with the run it was recorded from. Replays saved before rank was recorded
+    /// don’t have any, and so can’t be checked.
+    #[serde(default)]
+    pub ranks: Vec<f32>,
+}
+
+impl Replay {
+    /// Starts recording a run from the tick it is on.
+    pub fn new(game: &crate::Game) -> Self {
+        Self {
+            seed: game.seed(),
+            setup: game.setup(),
+            inputs: vec![],
+            ranks: vec![],
+        }
+    }
+
+    /// Records the inputs the game is about to be updated with, noting down its rank as well at
+    /// the start of every second.
+    pub fn record(&mut self, game: &crate::Game, inputs: Vec<crate::PlayerInput>) {
+        if self.inputs.len() % RANK_INTERVAL == 0 {
+            self.ranks.push(game.rank().value());
+        }
+
+        self.inputs.push(inputs);
+    }
+
+    /// Takes back everything recorded after the first `ticks`.
+    pub fn truncate(&mut self, ticks: usize) {
+        self.inputs.truncate(ticks);
+        self.ranks.truncate(ticks.div_ceil(RANK_INTERVAL));
+    }
+
+    /// Checks that a game being played back from the replay still has the rank it had when it was
+    /// recorded, as far as the replay knows. A replay only goes out of step if it was recorded on
+    /// a different version of the game, or was edited.
+    pub fn check(&self, game: &crate::Game) -> anyhow::Result<()> {
+        let tick = game.tick() as usize;
+
+        if tick % RANK_INTERVAL != 0 {
+            return Ok(());
+        }
+
+        match self.ranks.get(tick / RANK_INTERVAL) {
+            Some(rank) if rank.to_bits() != game.rank().value().to_bits() => {
+                anyhow::bail!("the replay went out of step by tick {}", tick)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(ron::de::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let replay = ron::ser::to_string(self)?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(path, replay)?;
+
+        Ok(())
+    }
+
+    /// Starts the run again from the beginning, ready for the recorded inputs to be played back
+    /// into it. Replays are always played on the [`PLAYFIELD`](crate::PLAYFIELD), like the runs
+    /// they were recorded from.
+    pub fn start(&self) -> crate::Game {
+        crate::Game::new(self.seed, self.setup, crate::PLAYFIELD)
+    }
+}
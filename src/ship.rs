@@ -1,36 +1,138 @@
 use {
-    crate::{Health, Sprite},
+    crate::Health,
     ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
 };
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ship {
     pos: crate::Point,
     velocity: crate::Vector,
-    sprite_cache: graphics::Image,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
     health: u16,
+    shot_type: crate::ShotType,
+    /// How many power items the ship has collected towards its power level.
+    power: u16,
+    lives: u8,
+    life_pieces: u8,
+    bombs: u8,
 }
 
-impl<'a> Ship {
-    const IMG_DATA: &'a [u8] = include_bytes!("ship.png");
+impl Ship {
+    const IMG_DATA: &[u8] = include_bytes!("ship.png");
+    const POWER_ITEMS_PER_LEVEL: u16 = 8;
+    const MAX_POWER: u16 =
+        Self::POWER_ITEMS_PER_LEVEL * (crate::ShotType::MAX_POWER_LEVEL as u16 - 1);
+    pub const LIFE_PIECES_PER_LIFE: u8 = 3;
+    pub const MAX_BOMBS: u8 = 8;
+    const MAX_HEALTH: u16 = 10;
 
-    pub fn new(pos: crate::Point, ctx: &mut ggez::Context) -> Self {
+    /// Creates a ship with as many lives and bombs as the difficulty starts the player off with.
+    pub fn new(pos: crate::Point, difficulty: &crate::DifficultySettings) -> Self {
         Self {
             pos,
             velocity: crate::Vector::new(0.0, 1.0),
-            sprite_cache: Self::sprite(Self::IMG_DATA, ctx).unwrap(),
+            sprite_cache: crate::SpriteCache::new(Self::IMG_DATA),
             health: Self::MAX_HEALTH,
+            shot_type: Default::default(),
+            power: 0,
+            lives: difficulty.starting_lives,
+            life_pieces: 0,
+            bombs: difficulty.starting_bombs.min(Self::MAX_BOMBS),
         }
     }
 
-    pub fn shoot(&self, ctx: &mut ggez::Context) -> crate::Laser {
-        crate::Laser::new(self.pos, ctx)
+    /// Gives the ship a particular number of lives and bombs and a particular power level, for
+    /// practising with.
+    pub fn with_loadout(mut self, lives: u8, bombs: u8, power_level: u8) -> Self {
+        let power_level = num::clamp(power_level, 1, crate::ShotType::MAX_POWER_LEVEL);
+
+        self.lives = lives;
+        self.bombs = bombs.min(Self::MAX_BOMBS);
+        self.power = u16::from(power_level - 1) * Self::POWER_ITEMS_PER_LEVEL;
+        self
     }
 
-    pub fn clamp_pos_to_screen(&mut self, ctx: &ggez::Context) {
-        let screen_dimens = graphics::screen_coordinates(ctx);
+    pub fn shoot(&self) -> Vec<crate::Laser> {
+        self.shot_type.shoot(self.pos, self.power_level())
+    }
+
+    /// How long the ship has to wait between volleys.
+    pub fn shot_cooldown(&self) -> Duration {
+        self.shot_type.cooldown(self.power_level())
+    }
+
+    pub fn shot_type(&self) -> crate::ShotType {
+        self.shot_type
+    }
+
+    pub fn switch_shot_type(&mut self) {
+        self.shot_type = self.shot_type.next();
+    }
 
-        self.pos.x = num::clamp(self.pos.x, 0.0, screen_dimens.w);
-        self.pos.y = num::clamp(self.pos.y, 0.0, screen_dimens.h);
+    /// The ship’s power level, from 1 up to the shot type’s maximum.
+    pub fn power_level(&self) -> u8 {
+        1 + (self.power / Self::POWER_ITEMS_PER_LEVEL) as u8
+    }
+
+    /// Extra lives left, not counting the one in play.
+    pub fn lives(&self) -> u8 {
+        self.lives
+    }
+
+    pub fn life_pieces(&self) -> u8 {
+        self.life_pieces
+    }
+
+    pub fn bombs(&self) -> u8 {
+        self.bombs
+    }
+
+    /// Adds a collected item to the ship’s power, lives or bombs.
+    pub fn collect(&mut self, item: crate::ItemKind) {
+        match item {
+            crate::ItemKind::Power => self.power = (self.power + 1).min(Self::MAX_POWER),
+            crate::ItemKind::Point | crate::ItemKind::Cancel => (),
+            crate::ItemKind::Bomb => self.bombs = (self.bombs + 1).min(Self::MAX_BOMBS),
+            crate::ItemKind::LifePiece => {
+                self.life_pieces += 1;
+
+                if self.life_pieces == Self::LIFE_PIECES_PER_LIFE {
+                    self.life_pieces = 0;
+                    self.lives += 1;
+                }
+            }
+        }
+    }
+
+    /// Uses up a bomb, if there are any left.
+    pub fn use_bomb(&mut self) -> bool {
+        if self.bombs == 0 {
+            return false;
+        }
+
+        self.bombs -= 1;
+        true
+    }
+
+    /// Brings a destroyed ship back with full health at the cost of a life and a power level, if
+    /// there are any lives left.
+    pub fn respawn(&mut self) -> bool {
+        if self.lives == 0 {
+            return false;
+        }
+
+        self.lives -= 1;
+        self.health = Self::MAX_HEALTH;
+        self.power = self.power.saturating_sub(Self::POWER_ITEMS_PER_LEVEL);
+        true
+    }
+
+    pub fn clamp_pos_to_screen(&mut self, screen: graphics::Rect) {
+        self.pos.x = num::clamp(self.pos.x, 0.0, screen.w);
+        self.pos.y = num::clamp(self.pos.y, 0.0, screen.h);
     }
 }
 
@@ -57,7 +159,9 @@ impl crate::Velocity for Ship {
 impl crate::StepDistance for Ship {}
 
 impl Health for Ship {
-    const MAX_HEALTH: u16 = 10;
+    fn max_health(&self) -> u16 {
+        Self::MAX_HEALTH
+    }
 
     fn health(&self) -> u16 {
         self.health
@@ -68,14 +172,20 @@ impl Health for Ship {
     }
 }
 
-impl Sprite for Ship {}
+impl crate::TakeDamage for Ship {}
 
-impl AsRef<graphics::Image> for Ship {
-    fn as_ref(&self) -> &graphics::Image {
+impl AsRef<crate::SpriteCache> for Ship {
+    fn as_ref(&self) -> &crate::SpriteCache {
         &self.sprite_cache
     }
 }
 
+impl crate::RestoreSprite for Ship {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(Self::IMG_DATA);
+    }
+}
+
 impl crate::Hitbox for Ship {
     const WIDTH_MUL: f32 = 0.5;
     const HEIGHT_MUL: f32 = 0.4;
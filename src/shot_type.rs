@@ -0,0 +1,124 @@
+use {
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// The kind of weapon the ship fires. Each shot type gets stronger as the ship’s power level goes
+/// up from 1 to [`ShotType::MAX_POWER_LEVEL`].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ShotType {
+    /// A fan of lasers that widens with power.
+    #[default]
+    Spread,
+    /// Needles that home in on the nearest enemy.
+    HomingNeedles,
+    /// A heavy laser that passes through enemies, hurting them for as long as it is inside them.
+    PiercingBeam,
+}
+
+/// What a shot type fires at a particular power level.
+struct Volley {
+    count: usize,
+    /// The angle the lasers are fanned out over, in radians.
+    spread: f32,
+    /// The speed of each laser, in units of the ship’s laser speed.
+    speed: f32,
+    damage: u16,
+    cooldown: Duration,
+}
+
+impl ShotType {
+    pub const MAX_POWER_LEVEL: u8 = 4;
+
+    fn volley(self, power_level: u8) -> Volley {
+        let power_level = num::clamp(power_level, 1, Self::MAX_POWER_LEVEL);
+
+        match self {
+            ShotType::Spread => Volley {
+                count: usize::from(power_level) * 2 - 1,
+                spread: f32::from(power_level - 1) * 0.2,
+                speed: 1.0,
+                damage: 1,
+                cooldown: Duration::from_millis(250),
+            },
+            ShotType::HomingNeedles => Volley {
+                count: if power_level >= 3 { 4 } else { 2 },
+                spread: 1.0,
+                speed: 0.7,
+                damage: if power_level >= 2 { 2 } else { 1 },
+                cooldown: Duration::from_millis(if power_level >= 4 { 200 } else { 300 }),
+            },
+            ShotType::PiercingBeam => Volley {
+                count: 1,
+                spread: 0.0,
+                speed: 1.2,
+                // Piercing beams deal their damage every tick they spend inside an enemy, so they
+                // don’t need to hit as hard as other shots.
+                damage: if power_level >= 3 { 2 } else { 1 },
+                cooldown: Duration::from_millis(match power_level {
+                    1 => 400,
+                    2 | 3 => 300,
+                    _ => 250,
+                }),
+            },
+        }
+    }
+
+    /// Fires a volley of lasers from `pos`.
+    pub fn shoot(self, pos: crate::Point, power_level: u8) -> Vec<crate::Laser> {
+        let volley = self.volley(power_level);
+
+        // Fan the lasers out evenly either side of straight up.
+        let gap = if volley.count > 1 {
+            volley.spread / (volley.count - 1) as f32
+        } else {
+            0.0
+        };
+        let first_angle = -std::f32::consts::FRAC_PI_2 - volley.spread / 2.0;
+
+        (0..volley.count)
+            .map(|i| {
+                let angle = first_angle + gap * i as f32;
+                let velocity = crate::Vector::new(angle.cos(), angle.sin()) * volley.speed;
+
+                crate::Laser::new(pos, velocity, volley.damage, self)
+            })
+            .collect()
+    }
+
+    pub fn cooldown(self, power_level: u8) -> Duration {
+        self.volley(power_level).cooldown
+    }
+
+    /// The shot type after this one, for switching between them.
+    pub fn next(self) -> Self {
+        match self {
+            ShotType::Spread => ShotType::HomingNeedles,
+            ShotType::HomingNeedles => ShotType::PiercingBeam,
+            ShotType::PiercingBeam => ShotType::Spread,
+        }
+    }
+
+    pub fn damage_kind(self) -> crate::DamageKind {
+        match self {
+            ShotType::HomingNeedles => crate::DamageKind::Kinetic,
+            ShotType::Spread | ShotType::PiercingBeam => crate::DamageKind::Energy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShotType::Spread => "Spread",
+            ShotType::HomingNeedles => "Homing needles",
+            ShotType::PiercingBeam => "Piercing beam",
+        }
+    }
+
+    pub fn img_data(self) -> &'static [u8] {
+        match self {
+            ShotType::Spread => include_bytes!("laser.png"),
+            ShotType::HomingNeedles => include_bytes!("needle.png"),
+            ShotType::PiercingBeam => include_bytes!("piercing_laser.png"),
+        }
+    }
+}
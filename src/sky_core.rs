@@ -1,58 +1,421 @@
 use {
-    crate::{Health, Sprite},
+    crate::Health,
     ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
 };
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SkyCore {
     pos: crate::Point,
-    velocity: crate::Vector,
-    sprite_cache: graphics::Image,
+    movement: crate::MovementScript,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
     health: u16,
+    max_health: u16,
+    time_since_telegraphed_attack: Duration,
+    telegraphed_attacks_made: u32,
+    pending_attacks: Vec<PendingAttack>,
+    new_telegraphs: Vec<crate::Telegraph>,
+    /// Whether the Sky Core has run out of time on its last phase and is flying away.
+    is_retreating: bool,
 }
 
-impl<'a> SkyCore {
-    const IMG_DATA: &'a [u8] = include_bytes!("sky_core.png");
+/// An attack that has been telegraphed, waiting for its warning to run out.
+#[derive(Clone, Serialize, Deserialize)]
+struct PendingAttack {
+    attack: TelegraphedAttack,
+    time_left: Duration,
+}
 
-    pub fn new(ctx: &mut ggez::Context) -> Self {
-        let screen_width = graphics::screen_coordinates(ctx).w;
+#[derive(Clone, Serialize, Deserialize)]
+enum TelegraphedAttack {
+    /// A fast stream of bullets along a line, like a laser.
+    Stream {
+        from: crate::Point,
+        direction: crate::Vector,
+    },
+    /// A ring of bullets bursting out of a spot.
+    Burst { pos: crate::Point },
+}
 
-        Self {
-            pos: crate::Point::new(screen_width / 2.0, 0.0),
-            velocity: crate::Vector::new(0.0, 1.0), // Move down the screen.
-            sprite_cache: Self::sprite(Self::IMG_DATA, ctx).unwrap(),
-            health: Self::MAX_HEALTH,
+impl TelegraphedAttack {
+    const STREAM_BULLETS: usize = 10;
+    const STREAM_MIN_SPEED: f32 = 3.0;
+    const STREAM_MAX_SPEED: f32 = 5.0;
+    const STREAM_COLOR: graphics::Color = graphics::Color::new(1.0, 0.3, 0.3, 1.0);
+    const BURST_BULLETS: usize = 10;
+    const BURST_SPEED: f32 = 1.2;
+    const BURST_RADIUS: f32 = 40.0;
+    const BURST_COLOR: graphics::Color = graphics::Color::new(1.0, 0.8, 0.3, 1.0);
+    /// How long each attack is warned about before it goes off. Bursts land wherever the ship
+    /// was, so they give a little longer to get out of the way.
+    const STREAM_LEAD_TIME: Duration = Duration::from_millis(1000);
+    const BURST_LEAD_TIME: Duration = Duration::from_millis(1200);
+
+    fn lead_time(&self) -> Duration {
+        match self {
+            TelegraphedAttack::Stream { .. } => Self::STREAM_LEAD_TIME,
+            TelegraphedAttack::Burst { .. } => Self::BURST_LEAD_TIME,
+        }
+    }
+
+    fn telegraph(&self, screen: graphics::Rect) -> crate::TelegraphKind {
+        match *self {
+            TelegraphedAttack::Stream { from, direction } => {
+                // Make sure the line reaches the edge of the screen wherever it starts.
+                let length = screen.w + screen.h;
+
+                crate::TelegraphKind::Line {
+                    from,
+                    to: from + direction * length,
+                }
+            }
+            TelegraphedAttack::Burst { pos } => crate::TelegraphKind::TargetCircle {
+                pos,
+                radius: Self::BURST_RADIUS,
+            },
         }
     }
 
-    pub fn shoot(&self, ctx: &mut ggez::Context) -> crate::Bullet {
-        crate::Bullet::new(self.pos, ctx)
+    fn launch(&self, difficulty: &crate::DifficultySettings) -> Vec<crate::Bullet> {
+        match *self {
+            TelegraphedAttack::Stream { from, direction } => {
+                // Firing the whole stream at once with a range of speeds stretches it out into a
+                // line.
+                let bullets = difficulty.bullet_count(Self::STREAM_BULLETS);
+                let speed_gap = (Self::STREAM_MAX_SPEED - Self::STREAM_MIN_SPEED)
+                    / bullets.saturating_sub(1).max(1) as f32;
+
+                (0..bullets)
+                    .map(|i| {
+                        let speed = Self::STREAM_MIN_SPEED + speed_gap * i as f32;
+
+                        crate::Bullet::with_velocity(from, direction * speed)
+                            .with_shape(crate::BulletShape::Rice)
+                            .with_color(Self::STREAM_COLOR)
+                    })
+                    .collect()
+            }
+            TelegraphedAttack::Burst { pos } => {
+                let bullets = difficulty.bullet_count(Self::BURST_BULLETS);
+
+                crate::Bullet::ring(pos, bullets, 0.0, Self::BURST_SPEED)
+                    .into_iter()
+                    .map(|bullet| {
+                        bullet
+                            .with_shape(crate::BulletShape::SmallRound)
+                            .with_color(Self::BURST_COLOR)
+                    })
+                    .collect()
+            }
+        }
     }
 }
 
-impl crate::Position for SkyCore {
-    fn pos(&self) -> crate::Point {
-        self.pos
+impl SkyCore {
+    const IMG_DATA: &[u8] = include_bytes!("sky_core.png");
+    /// How much health the Sky Core has on Normal.
+    const BASE_HEALTH: u16 = 100;
+    const SPEED: f32 = 10.0;
+    const HOVER_AMPLITUDE: f32 = 8.0;
+    const HOVER_PERIOD: Duration = Duration::from_secs(4);
+    const BULLET_TURN_RATE: f32 = 0.6;
+    const BULLET_ACCELERATION: f32 = 0.6;
+    const MAX_BULLET_SPEED: f32 = 3.0;
+    const CURVING_BULLET_LIFETIME: Duration = Duration::from_secs(8);
+    const CURVING_BULLET_COLOR: graphics::Color = graphics::Color::new(1.0, 0.5, 0.9, 1.0);
+    /// How likely the Sky Core is to fire a random bullet each tick on Normal.
+    const RANDOM_BULLET_CHANCE: f64 = 0.5;
+    const TELEGRAPHED_ATTACK_COOLDOWN: Duration = Duration::from_secs(3);
+    /// How fast the Sky Core flies away once it has run out of time, in pixels per second.
+    const RETREAT_SPEED: f32 = 120.0;
+
+    /// Creates a Sky Core that follows its entry movement, or by default marches down to the
+    /// centre of the screen, and then hovers there.
+    pub fn new(
+        pos: crate::Point,
+        entry: Option<crate::Movement>,
+        difficulty: &crate::DifficultySettings,
+        screen: graphics::Rect,
+    ) -> Self {
+        let entry = entry.unwrap_or_else(|| {
+            let target = crate::Point::new(pos.x, screen.h / 2.0);
+            let distance = (target - pos).norm();
+
+            crate::Movement::MoveTo {
+                target,
+                duration: Duration::from_secs_f32(distance / Self::SPEED),
+                easing: crate::Easing::Linear,
+            }
+        });
+        let hover = crate::Movement::Hover {
+            amplitude: crate::Vector::new(0.0, Self::HOVER_AMPLITUDE),
+            period: Self::HOVER_PERIOD,
+            duration: None,
+        };
+
+        let max_health = difficulty.max_health(Self::BASE_HEALTH);
+
+        Self {
+            pos,
+            movement: crate::MovementScript::new(vec![entry, hover]),
+            sprite_cache: crate::SpriteCache::new(Self::IMG_DATA),
+            health: max_health,
+            max_health,
+            time_since_telegraphed_attack: Duration::from_millis(0),
+            telegraphed_attacks_made: 0,
+            pending_attacks: vec![],
+            new_telegraphs: vec![],
+            is_retreating: false,
+        }
     }
 
-    fn pos_mut(&mut self) -> &mut crate::Point {
-        &mut self.pos
+    /// The Sky Core’s health is split evenly between its phases.
+    const PHASES: u16 = 3;
+    pub const ATTACKS: [crate::BossAttack; Self::PHASES as usize] = [
+        crate::BossAttack {
+            name: "Scattered Sky",
+            time_limit: Duration::from_secs(30),
+        },
+        crate::BossAttack {
+            name: "Curving Constellation",
+            time_limit: Duration::from_secs(40),
+        },
+        crate::BossAttack {
+            name: "Wraparound Storm",
+            time_limit: Duration::from_secs(50),
+        },
+    ];
+
+    /// The phase the Sky Core is in, counting from 1.
+    pub fn phase(&self) -> u16 {
+        // Round the number of phases left up so that a phase only ends once all of its health
+        // has been depleted.
+        let phases_left = (self.health * Self::PHASES).div_ceil(self.max_health);
+
+        (Self::PHASES - phases_left + 1).min(Self::PHASES)
+    }
+
+    /// Fires a bullet in a random direction some of the time, more often the denser the
+    /// difficulty’s bullet patterns are.
+    fn random_bullet(
+        &self,
+        rng: &mut crate::GameRng,
+        difficulty: &crate::DifficultySettings,
+    ) -> Option<crate::Bullet> {
+        use rand::Rng;
+
+        let chance = (Self::RANDOM_BULLET_CHANCE * f64::from(difficulty.bullet_density)).min(1.0);
+
+        if !rng.gen_bool(chance) {
+            return None;
+        }
+
+        let bullet = crate::Bullet::new(self.pos, rng);
+
+        // Bullets get harder to read as the Sky Core gets more desperate: first they curve, and
+        // then they speed up as well.
+        let bullet = match self.phase() {
+            1 => bullet,
+            phase => {
+                let turn_rate = if rng.gen() {
+                    Self::BULLET_TURN_RATE
+                } else {
+                    -Self::BULLET_TURN_RATE
+                };
+
+                let bullet = bullet
+                    .with_shape(crate::BulletShape::Star)
+                    .with_color(Self::CURVING_BULLET_COLOR)
+                    .with_behaviour(crate::BulletBehaviour::Curve(turn_rate))
+                    // Curving bullets can end up circling forever without ever leaving the screen.
+                    .with_behaviour(crate::BulletBehaviour::Lifetime(
+                        Self::CURVING_BULLET_LIFETIME,
+                    ));
+
+                if phase >= 3 {
+                    // Fast bullets would leave the screen too quickly to be a threat, so they
+                    // come back around from the other side.
+                    bullet
+                        .with_edge_policy(crate::EdgePolicy::WrapHorizontally(1))
+                        .with_behaviour(crate::BulletBehaviour::Accelerate(
+                            Self::BULLET_ACCELERATION,
+                        ))
+                        .with_behaviour(crate::BulletBehaviour::SpeedLimit {
+                            min: 0.0,
+                            max: Self::MAX_BULLET_SPEED,
+                        })
+                } else {
+                    bullet
+                }
+            }
+        };
+
+        Some(bullet)
+    }
+
+    /// Launches any telegraphed attacks whose warnings have run out, and from the second phase
+    /// onwards lines up a new one every so often.
+    fn update_telegraphed_attacks(
+        &mut self,
+        screen: graphics::Rect,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet> {
+        for pending_attack in &mut self.pending_attacks {
+            pending_attack.time_left = pending_attack.time_left.saturating_sub(delta_time);
+        }
+
+        let (due_attacks, pending_attacks): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.pending_attacks)
+                .into_iter()
+                .partition(|pending_attack| pending_attack.time_left == Duration::from_millis(0));
+        self.pending_attacks = pending_attacks;
+
+        let bullets = due_attacks
+            .into_iter()
+            .flat_map(|pending_attack| pending_attack.attack.launch(difficulty))
+            .collect();
+
+        self.time_since_telegraphed_attack += delta_time;
+
+        if self.phase() >= 2
+            && self.time_since_telegraphed_attack >= Self::TELEGRAPHED_ATTACK_COOLDOWN
+        {
+            self.time_since_telegraphed_attack = Duration::from_millis(0);
+            self.telegraphed_attacks_made += 1;
+
+            // In the last phase, every other attack bursts out of wherever the ship was instead.
+            let attack = if self.phase() >= 3 && self.telegraphed_attacks_made % 2 == 0 {
+                TelegraphedAttack::Burst { pos: target }
+            } else {
+                let to_target = target - self.pos;
+                let direction = if to_target == crate::Vector::zeros() {
+                    crate::Vector::new(0.0, 1.0)
+                } else {
+                    to_target.normalize()
+                };
+
+                TelegraphedAttack::Stream {
+                    from: self.pos,
+                    direction,
+                }
+            };
+
+            self.new_telegraphs.push(crate::Telegraph::new(
+                attack.telegraph(screen),
+                attack.lead_time(),
+            ));
+            self.pending_attacks.push(PendingAttack {
+                time_left: attack.lead_time(),
+                attack,
+            });
+        }
+
+        bullets
+    }
+}
+
+impl From<SkyCore> for crate::SavedEnemy {
+    fn from(enemy: SkyCore) -> Self {
+        Self::SkyCore(enemy)
     }
 }
 
-impl crate::Velocity for SkyCore {
-    fn velocity(&self) -> crate::Vector {
-        self.velocity
+impl crate::EnemyBehaviour for SkyCore {
+    const SCORE_VALUE: u32 = 10_000;
+    const SHOWS_HEALTH_BAR: bool = true;
+    const HIT_DROP_CHANCE: f64 = 0.05;
+
+    fn movement(&mut self, _screen: graphics::Rect, delta_time: Duration) {
+        self.movement.update(&mut self.pos, delta_time);
+    }
+
+    fn attack(
+        &mut self,
+        screen: graphics::Rect,
+        rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet> {
+        if self.is_retreating {
+            return vec![];
+        }
+
+        let mut bullets = self.update_telegraphed_attacks(screen, target, difficulty, delta_time);
+        bullets.extend(self.random_bullet(rng, difficulty));
+
+        bullets
+    }
+
+    fn telegraphs(&mut self) -> Vec<crate::Telegraph> {
+        std::mem::take(&mut self.new_telegraphs)
+    }
+
+    fn drops(&self, _rng: &mut crate::GameRng) -> Vec<crate::ItemKind> {
+        let mut drops = vec![crate::ItemKind::Power; 4];
+        drops.extend(vec![crate::ItemKind::Point; 6]);
+        drops.extend(vec![crate::ItemKind::Bomb, crate::ItemKind::LifePiece]);
+
+        drops
+    }
+
+    fn boss_phase(&self) -> Option<u16> {
+        // A Sky Core that is getting away isn’t a boss to fight any more.
+        if self.is_retreating {
+            None
+        } else {
+            Some(self.phase())
+        }
+    }
+
+    fn boss_attack(&self) -> Option<crate::BossAttack> {
+        self.boss_phase()
+            .map(|phase| Self::ATTACKS[usize::from(phase - 1)])
+    }
+
+    fn end_boss_phase(&mut self) {
+        // The attacks lined up during the phase go with it, just like its bullets.
+        self.pending_attacks.clear();
+
+        // Drop the health to where the next phase starts, or get away in the last phase.
+        let phases_left = Self::PHASES - self.phase();
+
+        if phases_left > 0 {
+            self.health = self.max_health * phases_left / Self::PHASES;
+            return;
+        }
+
+        self.is_retreating = true;
+        self.movement = crate::MovementScript::new(vec![crate::Movement::Linear {
+            velocity: crate::Vector::new(0.0, -Self::RETREAT_SPEED),
+        }]);
     }
 
-    fn velocity_mut(&mut self) -> &mut crate::Vector {
-        &mut self.velocity
+    fn has_left_screen(&self, screen: graphics::Rect) -> bool {
+        use crate::OnScreen;
+
+        self.is_retreating && !self.is_on_screen(screen)
     }
 }
 
-impl crate::StepDistance for SkyCore {}
+impl crate::Position for SkyCore {
+    fn pos(&self) -> crate::Point {
+        self.pos
+    }
+
+    fn pos_mut(&mut self) -> &mut crate::Point {
+        &mut self.pos
+    }
+}
 
 impl Health for SkyCore {
-    const MAX_HEALTH: u16 = 100;
+    fn max_health(&self) -> u16 {
+        self.max_health
+    }
 
     fn health(&self) -> u16 {
         self.health
@@ -63,12 +426,27 @@ impl Health for SkyCore {
     }
 }
 
-impl Sprite for SkyCore {}
+impl crate::TakeDamage for SkyCore {
+    fn damage_multiplier(&self, _kind: crate::DamageKind) -> f32 {
+        // A Sky Core that got away can’t be shot down for the reward after all.
+        if self.is_retreating {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
 
-impl AsRef<graphics::Image> for SkyCore {
-    fn as_ref(&self) -> &graphics::Image {
+impl AsRef<crate::SpriteCache> for SkyCore {
+    fn as_ref(&self) -> &crate::SpriteCache {
         &self.sprite_cache
     }
 }
 
+impl crate::RestoreSprite for SkyCore {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(Self::IMG_DATA);
+    }
+}
+
 impl crate::Hitbox for SkyCore {}
@@ -1,25 +1,76 @@
-use ggez::graphics;
+use {
+    ggez::graphics,
+    image::ImageDecoder,
+    std::{cell::OnceCell, convert::TryInto},
+};
 
-pub trait Sprite {
-    fn sprite(img_data: &[u8], ctx: &mut ggez::Context) -> anyhow::Result<graphics::Image> {
-        use {
-            image::ImageDecoder,
-            std::{convert::TryInto, io::Read},
-        };
+/// An entity’s sprite. Playing the game only needs to know how big sprites are, so the image itself
+/// isn’t made until the sprite is first drawn, which lets the game be played without a window.
+///
+/// Sprites are left out when entities are saved, since they can always be made again from the
+/// entity’s own image data, so a loaded entity has an empty sprite until
+/// [`RestoreSprite::restore_sprite`] is called on it.
+#[derive(Clone, Default)]
+pub struct SpriteCache {
+    img_data: &'static [u8],
+    width: u16,
+    height: u16,
+    image: OnceCell<graphics::Image>,
+}
+
+impl SpriteCache {
+    pub fn new(img_data: &'static [u8]) -> Self {
+        // The sprites are baked into the executable, so failing to read one is a bug.
+        let (width, height) = Self::decoder(img_data).dimensions();
+
+        Self {
+            img_data,
+            width: width.try_into().expect("sprite too wide"),
+            height: height.try_into().expect("sprite too tall"),
+            image: OnceCell::new(),
+        }
+    }
+
+    fn decoder(img_data: &[u8]) -> image::png::PngDecoder<&[u8]> {
+        image::png::PngDecoder::new(img_data).expect("invalid sprite data")
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
 
-        let decoder = image::png::PngDecoder::new(img_data)?;
+    pub fn dimensions(&self) -> graphics::Rect {
+        graphics::Rect::new(0.0, 0.0, self.width.into(), self.height.into())
+    }
 
-        let (width, height) = decoder.dimensions();
-        let (width, height): (u16, u16) = (width.try_into()?, height.try_into()?);
+    /// The sprite’s image, which is made the first time it is asked for.
+    pub fn image(&self, ctx: &mut ggez::Context) -> ggez::GameResult<&graphics::Image> {
+        use std::io::Read;
+
+        if let Some(image) = self.image.get() {
+            return Ok(image);
+        }
 
         let mut rgba = vec![];
-        decoder.into_reader()?.read_to_end(&mut rgba)?;
+        Self::decoder(self.img_data)
+            .into_reader()
+            .and_then(|mut reader| Ok(reader.read_to_end(&mut rgba)?))
+            .expect("invalid sprite data");
 
-        let mut image = graphics::Image::from_rgba8(ctx, width, height, &rgba)?;
+        let mut image = graphics::Image::from_rgba8(ctx, self.width, self.height, &rgba)?;
 
         // Disable antialiasing as all sprites are in 8-bit style.
         image.set_filter(graphics::FilterMode::Nearest);
 
-        Ok(image)
+        Ok(self.image.get_or_init(|| image))
     }
 }
+
+/// Something with a sprite that has to be made again after it is loaded.
+pub trait RestoreSprite {
+    fn restore_sprite(&mut self);
+}
@@ -0,0 +1,518 @@
+use {
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::{sync::OnceLock, time::Duration},
+};
+
+const STAGE_DATA: &str = include_str!("stages.ron");
+
+/// A stage’s timeline of events, as written in the stage data file.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Stage {
+    pub name: String,
+    /// How long before enemies arrive from off-screen that arrows warn about them, in seconds.
+    #[serde(default = "default_spawn_warning")]
+    pub spawn_warning: f32,
+    pub events: Vec<TimedEvent>,
+}
+
+fn default_spawn_warning() -> f32 {
+    1.0
+}
+
+impl Stage {
+    /// Every stage of a run, in the order they are played. They are only loaded once, so that
+    /// games and the copies kept of them can share them rather than each having their own.
+    pub fn all() -> &'static [Self] {
+        static STAGES: OnceLock<Vec<Stage>> = OnceLock::new();
+
+        // The stages are baked into the executable, so failing to load them is a bug.
+        STAGES.get_or_init(|| Self::load_all().expect("invalid stage data"))
+    }
+
+    fn load_all() -> anyhow::Result<Vec<Self>> {
+        let mut stages: Vec<Self> = ron::de::from_str(STAGE_DATA)?;
+
+        for stage in &mut stages {
+            stage.events.sort_by(|a, b| a.at.total_cmp(&b.at));
+        }
+
+        Ok(stages)
+    }
+
+    /// The kinds of the stage’s mid-bosses and bosses, in the order they appear.
+    pub fn bosses(&self) -> Vec<EnemyKind> {
+        self.events
+            .iter()
+            .filter_map(|timed_event| match timed_event.event {
+                StageEvent::StartMidBoss { enemy, .. } | StageEvent::StartBoss { enemy, .. } => {
+                    Some(enemy)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct TimedEvent {
+    /// When the event happens, in seconds of stage time. Stage time stands still during dialogue
+    /// and boss fights.
+    pub at: f32,
+    pub event: StageEvent,
+}
+
+/// Positions and directions in stage data are given as fractions of the screen’s width and height,
+/// so that stages play the same regardless of the size of the window.
+pub type ScreenFraction = (f32, f32);
+
+#[derive(Clone, Deserialize, Serialize)]
+pub enum StageEvent {
+    SpawnWave {
+        enemy: EnemyKind,
+        formation: Formation,
+        #[serde(default = "default_heading")]
+        heading: ScreenFraction,
+        /// A path for each enemy to follow as it enters, before heading off in the direction of
+        /// `heading`.
+        #[serde(default)]
+        path: Option<WavePath>,
+    },
+    /// Holds the timeline until the mid-boss is defeated or its attack runs out of time and it
+    /// retreats, after which the stage carries on while it leaves.
+    StartMidBoss {
+        enemy: EnemyKind,
+        pos: ScreenFraction,
+    },
+    /// Holds the timeline for the duration of the line (in seconds).
+    Dialogue {
+        speaker: String,
+        text: String,
+        duration: f32,
+    },
+    /// Holds the timeline until the boss is defeated or its last attack runs out of time.
+    StartBoss {
+        enemy: EnemyKind,
+        pos: ScreenFraction,
+    },
+    EndStage,
+}
+
+fn default_heading() -> ScreenFraction {
+    (0.0, 1.0) // Move down the screen.
+}
+
+/// A path given relative to each enemy’s place in its formation, so that the formation keeps its
+/// shape along the way.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct WavePath {
+    pub curve: Curve,
+    pub points: Vec<ScreenFraction>,
+    /// How long the path takes to follow, in seconds.
+    pub duration: f32,
+    #[serde(default)]
+    pub easing: crate::Easing,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum Curve {
+    Bezier,
+    CatmullRom,
+}
+
+impl StageEvent {
+    /// Whether the event stops the timeline from carrying on until it is over.
+    pub fn holds_timeline(&self) -> bool {
+        match self {
+            StageEvent::SpawnWave { .. } => false,
+            StageEvent::StartMidBoss { .. }
+            | StageEvent::StartBoss { .. }
+            | StageEvent::Dialogue { .. }
+            | StageEvent::EndStage => true,
+        }
+    }
+
+    /// Creates arrows warning about the enemies that this event will bring in from off-screen,
+    /// `lead_time` before they arrive.
+    pub fn spawn_telegraphs(
+        &self,
+        lead_time: Duration,
+        screen: graphics::Rect,
+    ) -> Vec<crate::Telegraph> {
+        let to_point = |(x, y): ScreenFraction| crate::Point::new(x * screen.w, y * screen.h);
+
+        let positions = match self {
+            StageEvent::SpawnWave { formation, .. } => formation.positions(),
+            StageEvent::StartMidBoss { pos, .. } | StageEvent::StartBoss { pos, .. } => vec![*pos],
+            StageEvent::Dialogue { .. } | StageEvent::EndStage => vec![],
+        };
+
+        positions
+            .into_iter()
+            .filter_map(|pos| crate::Telegraph::edge_arrow(to_point(pos), lead_time, screen))
+            .collect()
+    }
+
+    /// Creates the enemies that this event brings into play.
+    pub fn spawn_enemies(
+        &self,
+        difficulty: &crate::DifficultySettings,
+        screen: graphics::Rect,
+    ) -> Vec<Box<dyn crate::Enemy>> {
+        let to_vector = |(x, y): ScreenFraction| crate::Vector::new(x * screen.w, y * screen.h);
+        let to_point = |fraction| crate::Point::from(to_vector(fraction));
+
+        match self {
+            StageEvent::SpawnWave {
+                enemy,
+                formation,
+                heading,
+                path,
+            } => {
+                let heading = {
+                    let heading = to_vector(*heading);
+
+                    if heading == crate::Vector::zeros() {
+                        heading
+                    } else {
+                        heading.normalize()
+                    }
+                };
+
+                let entry = path.as_ref().map(|path| {
+                    let points = path.points.iter().copied().map(to_vector).collect();
+
+                    crate::Movement::FollowPath {
+                        path: match path.curve {
+                            Curve::Bezier => crate::Path::Bezier(points),
+                            Curve::CatmullRom => crate::Path::CatmullRom(points),
+                        },
+                        duration: Duration::from_secs_f32(path.duration),
+                        easing: path.easing,
+                    }
+                });
+
+                formation
+                    .positions()
+                    .into_iter()
+                    .map(|pos| {
+                        enemy.spawn(to_point(pos), heading, entry.clone(), difficulty, screen)
+                    })
+                    .collect()
+            }
+            StageEvent::StartMidBoss { enemy, pos, .. } | StageEvent::StartBoss { enemy, pos } => {
+                vec![enemy.spawn(
+                    to_point(*pos),
+                    crate::Vector::new(0.0, 1.0),
+                    None,
+                    difficulty,
+                    screen,
+                )]
+            }
+            StageEvent::Dialogue { .. } | StageEvent::EndStage => vec![],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EnemyKind {
+    PopcornFighter,
+    Turret,
+    Gunship,
+    SkyCore,
+}
+
+impl EnemyKind {
+    pub const ALL: [EnemyKind; 4] = [
+        EnemyKind::PopcornFighter,
+        EnemyKind::Turret,
+        EnemyKind::Gunship,
+        EnemyKind::SkyCore,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EnemyKind::PopcornFighter => "Popcorn Fighter",
+            EnemyKind::Turret => "Turret",
+            EnemyKind::Gunship => "Gunship",
+            EnemyKind::SkyCore => "Sky Core",
+        }
+    }
+
+    /// The attacks the enemy uses in each of its phases, if it is a boss.
+    pub fn boss_attacks(self) -> &'static [crate::BossAttack] {
+        match self {
+            EnemyKind::PopcornFighter | EnemyKind::Turret => &[],
+            EnemyKind::Gunship => &crate::Gunship::ATTACKS,
+            EnemyKind::SkyCore => &crate::SkyCore::ATTACKS,
+        }
+    }
+
+    /// Creates an enemy of this kind. Only popcorn fighters fly off in the direction of `heading`
+    /// once they have finished their entry movement; everything else has its own idea of where to
+    /// go.
+    pub fn spawn(
+        self,
+        pos: crate::Point,
+        heading: crate::Vector,
+        entry: Option<crate::Movement>,
+        difficulty: &crate::DifficultySettings,
+        screen: graphics::Rect,
+    ) -> Box<dyn crate::Enemy> {
+        match self {
+            EnemyKind::PopcornFighter => {
+                Box::new(crate::PopcornFighter::new(pos, heading, entry, difficulty))
+            }
+            EnemyKind::Turret => Box::new(crate::Turret::new(pos, entry, difficulty)),
+            EnemyKind::Gunship => Box::new(crate::Gunship::new(pos, entry, difficulty, screen)),
+            EnemyKind::SkyCore => Box::new(crate::SkyCore::new(pos, entry, difficulty, screen)),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub enum Formation {
+    Single {
+        pos: ScreenFraction,
+    },
+    /// Enemies spread evenly along the line from `from` to `to`.
+    Line {
+        count: usize,
+        from: ScreenFraction,
+        to: ScreenFraction,
+    },
+    /// A V with its point at `apex`, with each pair of enemies behind it `spacing` further back.
+    V {
+        count: usize,
+        apex: ScreenFraction,
+        spacing: ScreenFraction,
+    },
+}
+
+impl Formation {
+    pub fn positions(&self) -> Vec<ScreenFraction> {
+        match *self {
+            Formation::Single { pos } => vec![pos],
+            Formation::Line { count, from, to } => {
+                // A single enemy has nothing to spread out from, so it goes at the start of the
+                // line.
+                let steps = count.saturating_sub(1).max(1) as f32;
+
+                (0..count)
+                    .map(|i| {
+                        let t = i as f32 / steps;
+                        (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+                    })
+                    .collect()
+            }
+            Formation::V {
+                count,
+                apex,
+                spacing,
+            } => (0..count)
+                .map(|i| {
+                    // Alternate between the left and right arms of the V.
+                    let rank = i.div_ceil(2) as f32;
+                    let side = match i % 2 {
+                        0 => 1.0,
+                        _ => -1.0,
+                    };
+
+                    (apex.0 + side * rank * spacing.0, apex.1 - rank * spacing.1)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+enum Hold {
+    /// Lasts for as long as the boss fights, which is up to the time limits of its attacks.
+    Boss,
+    Dialogue {
+        speaker: String,
+        text: String,
+        time_left: Duration,
+    },
+}
+
+/// Plays through a stage’s timeline, handing out events as their time comes.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StageRunner {
+    /// Which of the stages is being played.
+    stage_index: usize,
+    time: Duration,
+    next_event: usize,
+    next_warning: usize,
+    hold: Option<Hold>,
+    is_finished: bool,
+}
+
+impl StageRunner {
+    pub fn new(stage_index: usize) -> Self {
+        Self {
+            stage_index,
+            time: Duration::from_millis(0),
+            next_event: 0,
+            next_warning: 0,
+            hold: None,
+            is_finished: false,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.stage().name
+    }
+
+    fn stage(&self) -> &'static Stage {
+        &Stage::all()[self.stage_index]
+    }
+
+    /// Jumps ahead to just before the stage’s `index`th boss (counting mid-bosses, from 0) turns
+    /// up, skipping everything before it.
+    pub fn skip_to_boss(&mut self, index: usize) {
+        let boss_event = self
+            .stage()
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, timed_event)| {
+                matches!(
+                    timed_event.event,
+                    StageEvent::StartMidBoss { .. } | StageEvent::StartBoss { .. }
+                )
+            })
+            .nth(index);
+
+        if let Some((event_index, timed_event)) = boss_event {
+            self.time = Duration::from_secs_f32(timed_event.at);
+            self.next_event = event_index;
+            self.next_warning = event_index;
+        }
+    }
+
+    /// How much stage time has passed since the stage started.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    /// The line of dialogue currently being shown, along with who is saying it.
+    pub fn dialogue(&self) -> Option<(&str, &str)> {
+        match &self.hold {
+            Some(Hold::Dialogue { speaker, text, .. }) => Some((speaker, text)),
+            _ => None,
+        }
+    }
+
+    /// Advances the timeline by a tick, returning the events that happen during it.
+    pub fn update(&mut self, delta_time: Duration, is_boss_alive: bool) -> Vec<StageEvent> {
+        if self.is_finished || self.is_held(delta_time, is_boss_alive) {
+            return vec![];
+        }
+
+        self.time += delta_time;
+
+        let mut events = vec![];
+
+        while let Some(timed_event) = self.stage().events.get(self.next_event) {
+            if timed_event.at > self.time.as_secs_f32() {
+                break;
+            }
+
+            self.next_event += 1;
+
+            let event = timed_event.event.clone();
+
+            match &event {
+                StageEvent::StartMidBoss { .. } | StageEvent::StartBoss { .. } => {
+                    self.hold = Some(Hold::Boss)
+                }
+                StageEvent::Dialogue {
+                    speaker,
+                    text,
+                    duration,
+                } => {
+                    self.hold = Some(Hold::Dialogue {
+                        speaker: speaker.clone(),
+                        text: text.clone(),
+                        time_left: Duration::from_secs_f32(*duration),
+                    })
+                }
+                StageEvent::EndStage => self.is_finished = true,
+                StageEvent::SpawnWave { .. } => (),
+            }
+
+            events.push(event);
+
+            // Anything after a hold has to wait until the hold is over.
+            if self.hold.is_some() || self.is_finished {
+                break;
+            }
+        }
+
+        events
+    }
+
+    /// Events coming up within the stage’s warning time that haven’t been warned about yet, along
+    /// with how long it is until each of them.
+    pub fn new_warnings(&mut self) -> Vec<(Duration, StageEvent)> {
+        let mut warnings = vec![];
+
+        if self.is_finished || self.hold.is_some() {
+            return warnings;
+        }
+
+        self.next_warning = self.next_warning.max(self.next_event);
+
+        // Anything after an upcoming hold could be any amount of time away, so it can’t be warned
+        // about until the hold is over.
+        let is_behind_hold = self.stage().events[self.next_event..self.next_warning]
+            .iter()
+            .any(|timed_event| timed_event.event.holds_timeline());
+
+        if is_behind_hold {
+            return warnings;
+        }
+
+        let time = self.time.as_secs_f32();
+
+        while let Some(timed_event) = self.stage().events.get(self.next_warning) {
+            if timed_event.at > time + self.stage().spawn_warning {
+                break;
+            }
+
+            self.next_warning += 1;
+
+            let time_until = Duration::from_secs_f32((timed_event.at - time).max(0.0));
+            warnings.push((time_until, timed_event.event.clone()));
+
+            if timed_event.event.holds_timeline() {
+                break;
+            }
+        }
+
+        warnings
+    }
+
+    /// Counts down any hold on the timeline, releasing it once it is over.
+    fn is_held(&mut self, delta_time: Duration, is_boss_alive: bool) -> bool {
+        let is_over = match &mut self.hold {
+            None => return false,
+            Some(Hold::Boss) => !is_boss_alive,
+            Some(Hold::Dialogue { time_left, .. }) => {
+                *time_left = time_left.saturating_sub(delta_time);
+                *time_left == Duration::from_millis(0)
+            }
+        };
+
+        if is_over {
+            self.hold = None;
+        }
+
+        !is_over
+    }
+}
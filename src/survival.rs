@@ -0,0 +1,154 @@
+use {
+    crate::{EnemyKind, Formation, StageEvent},
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+const FIRST_WAVE_DELAY: Duration = Duration::from_secs(2);
+const START_WAVE_INTERVAL: f32 = 6.0;
+const MIN_WAVE_INTERVAL: f32 = 1.5;
+/// How much sooner each wave comes than the one before it, in seconds.
+const WAVE_INTERVAL_DECAY: f32 = 0.15;
+/// How long before a wave arrives that arrows warn about it.
+const SPAWN_WARNING: Duration = Duration::from_secs(1);
+/// Every so often a gunship joins the wave.
+const WAVES_PER_GUNSHIP: u32 = 12;
+/// How many more bullets are in each volley for every minute survived, as a fraction of the usual
+/// amount.
+const DENSITY_GAIN_PER_MINUTE: f32 = 0.3;
+/// How much faster bullets get for every minute survived, as a fraction of their usual speed.
+const SPEED_GAIN_PER_MINUTE: f32 = 0.15;
+const MAX_SPEED_MUL: f32 = 2.0;
+
+/// Runs survival mode, sending in randomly made waves of enemies that come thicker and faster
+/// for as long as the player holds out. Waves are made with the game’s seeded RNG, so a survival
+/// run can be reproduced from its seed like any other.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SurvivalDirector {
+    time: Duration,
+    time_until_wave: Duration,
+    next_wave: Option<StageEvent>,
+    has_warned: bool,
+    waves_sent: u32,
+}
+
+impl Default for SurvivalDirector {
+    fn default() -> Self {
+        Self {
+            time: Duration::from_millis(0),
+            time_until_wave: FIRST_WAVE_DELAY,
+            next_wave: None,
+            has_warned: false,
+            waves_sent: 0,
+        }
+    }
+}
+
+impl SurvivalDirector {
+    /// How long the player has survived.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Advances by a tick, returning the wave that arrives during it, if any.
+    pub fn update(&mut self, delta_time: Duration, rng: &mut crate::GameRng) -> Vec<StageEvent> {
+        self.time += delta_time;
+        self.time_until_wave = self.time_until_wave.saturating_sub(delta_time);
+
+        // Plan the next wave ahead of time, so that it can be warned about.
+        if self.next_wave.is_none() {
+            self.next_wave = Some(self.make_wave(rng));
+        }
+
+        if self.time_until_wave > Duration::from_millis(0) {
+            return vec![];
+        }
+
+        self.waves_sent += 1;
+        self.has_warned = false;
+
+        let interval = (START_WAVE_INTERVAL - WAVE_INTERVAL_DECAY * self.waves_sent as f32)
+            .max(MIN_WAVE_INTERVAL);
+        self.time_until_wave = Duration::from_secs_f32(interval);
+
+        self.next_wave.take().into_iter().collect()
+    }
+
+    /// The next wave, along with how long it is until it arrives, once it is close enough to be
+    /// warned about. Each wave is only warned about once.
+    pub fn new_warnings(&mut self) -> Vec<(Duration, StageEvent)> {
+        match &self.next_wave {
+            Some(wave) if !self.has_warned && self.time_until_wave <= SPAWN_WARNING => {
+                self.has_warned = true;
+                vec![(self.time_until_wave, wave.clone())]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Makes the difficulty’s bullets thicker and faster the longer the player has survived.
+    pub fn apply(&self, difficulty: &crate::DifficultySettings) -> crate::DifficultySettings {
+        let minutes = self.time.as_secs_f32() / 60.0;
+
+        crate::DifficultySettings {
+            bullet_speed: difficulty.bullet_speed
+                * (1.0 + SPEED_GAIN_PER_MINUTE * minutes).min(MAX_SPEED_MUL),
+            bullet_density: difficulty.bullet_density * (1.0 + DENSITY_GAIN_PER_MINUTE * minutes),
+            ..*difficulty
+        }
+    }
+
+    /// Makes up a wave that gets bigger the more waves have been sent.
+    fn make_wave(&self, rng: &mut crate::GameRng) -> StageEvent {
+        use rand::Rng;
+
+        let wave_number = self.waves_sent + 1;
+
+        if wave_number % WAVES_PER_GUNSHIP == 0 {
+            return StageEvent::SpawnWave {
+                enemy: EnemyKind::Gunship,
+                formation: Formation::Single {
+                    pos: (rng.gen_range(0.3, 0.7), -0.05),
+                },
+                heading: (0.0, 1.0),
+                path: None,
+            };
+        }
+
+        // Turrets only start turning up once the player has got going.
+        let enemy = if wave_number >= 3 && rng.gen_ratio(1, 3) {
+            EnemyKind::Turret
+        } else {
+            EnemyKind::PopcornFighter
+        };
+        let count = match enemy {
+            EnemyKind::Turret => 1 + (wave_number / 8).min(3) as usize,
+            _ => 3 + (wave_number / 3).min(7) as usize,
+        };
+
+        // Come in from either side of the top of the screen, slanting across it.
+        let is_from_left = rng.gen();
+        let (from, to, heading) = if is_from_left {
+            ((0.1, -0.05), (0.45, -0.05), (rng.gen_range(0.0, 0.4), 1.0))
+        } else {
+            ((0.9, -0.05), (0.55, -0.05), (rng.gen_range(-0.4, 0.0), 1.0))
+        };
+
+        let formation = if matches!(enemy, EnemyKind::PopcornFighter) && rng.gen() {
+            Formation::V {
+                count,
+                apex: (rng.gen_range(0.25, 0.75), -0.05),
+                spacing: (0.05, 0.04),
+            }
+        } else {
+            Formation::Line { count, from, to }
+        };
+
+        StageEvent::SpawnWave {
+            enemy,
+            formation,
+            heading,
+            path: None,
+        }
+    }
+}
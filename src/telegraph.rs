@@ -0,0 +1,163 @@
+use {
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+const WARNING_COLOR: (u8, u8, u8) = (229, 37, 72);
+const LINE_WIDTH: f32 = 2.0;
+const FLASHES_PER_SECOND: f32 = 6.0;
+const CIRCLE_TOLERANCE: f32 = 0.5;
+const ARROW_SIZE: f32 = 16.0;
+/// How far in from the edge of the screen arrows are drawn, so that they are fully visible.
+const ARROW_MARGIN: f32 = ARROW_SIZE;
+
+/// A warning shown ahead of an attack, so that the player has a chance to get out of the way.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Telegraph {
+    kind: TelegraphKind,
+    lead_time: Duration,
+    age: Duration,
+    /// The enemy whose attack is being warned about, if the warning should go when that enemy
+    /// does.
+    owner: Option<crate::EnemyId>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum TelegraphKind {
+    /// A line along which an aimed attack is about to travel.
+    Line {
+        from: crate::Point,
+        to: crate::Point,
+    },
+    /// A flashing circle around a spot that is about to be hit.
+    TargetCircle { pos: crate::Point, radius: f32 },
+    /// A flashing arrow on the edge of the screen, pointing at something that is about to arrive
+    /// from off-screen.
+    EdgeArrow {
+        pos: crate::Point,
+        direction: crate::Vector,
+    },
+}
+
+impl Telegraph {
+    /// Creates a warning that lasts for `lead_time`, which should be how long it is until the
+    /// attack it warns about.
+    pub fn new(kind: TelegraphKind, lead_time: Duration) -> Self {
+        Self {
+            kind,
+            lead_time,
+            age: Duration::from_millis(0),
+            owner: None,
+        }
+    }
+
+    /// Creates an arrow on the edge of the screen nearest to `pos` that points at it, or nothing if
+    /// `pos` is already on the screen.
+    pub fn edge_arrow(
+        pos: crate::Point,
+        lead_time: Duration,
+        screen: graphics::Rect,
+    ) -> Option<Self> {
+        let is_on_screen = pos.x >= screen.x
+            && pos.x <= screen.x + screen.w
+            && pos.y >= screen.y
+            && pos.y <= screen.y + screen.h;
+
+        if is_on_screen {
+            return None;
+        }
+
+        let edge_pos = crate::Point::new(
+            num::clamp(
+                pos.x,
+                screen.x + ARROW_MARGIN,
+                screen.x + screen.w - ARROW_MARGIN,
+            ),
+            num::clamp(
+                pos.y,
+                screen.y + ARROW_MARGIN,
+                screen.y + screen.h - ARROW_MARGIN,
+            ),
+        );
+
+        Some(Self::new(
+            TelegraphKind::EdgeArrow {
+                pos: edge_pos,
+                direction: (pos - edge_pos).normalize(),
+            },
+            lead_time,
+        ))
+    }
+
+    pub fn with_owner(mut self, owner: crate::EnemyId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn owner(&self) -> Option<crate::EnemyId> {
+        self.owner
+    }
+
+    pub fn update(&mut self, delta_time: Duration) {
+        self.age += delta_time;
+    }
+
+    /// Whether the attack has arrived, and so the warning is no longer needed.
+    pub fn is_finished(&self) -> bool {
+        self.age >= self.lead_time
+    }
+
+    pub fn draw(&self, ctx: &mut ggez::Context) -> ggez::GameResult {
+        let (r, g, b) = WARNING_COLOR;
+        let is_flash_on = (self.age.as_secs_f32() * FLASHES_PER_SECOND).fract() < 0.5;
+
+        let mut builder = graphics::MeshBuilder::new();
+
+        match self.kind {
+            // Lines fade in as the attack draws near rather than flashing, so that the player can
+            // judge when it will go off.
+            TelegraphKind::Line { from, to } => {
+                let progress = if self.lead_time == Duration::from_millis(0) {
+                    1.0
+                } else {
+                    (self.age.as_secs_f32() / self.lead_time.as_secs_f32()).min(1.0)
+                };
+                let color = graphics::Color::from_rgba(r, g, b, (progress * 255.0) as u8);
+
+                builder.line(&[from, to], LINE_WIDTH, color)?;
+            }
+            TelegraphKind::TargetCircle { pos, radius } => {
+                if !is_flash_on {
+                    return Ok(());
+                }
+
+                builder.circle(
+                    graphics::DrawMode::stroke(LINE_WIDTH),
+                    pos,
+                    radius,
+                    CIRCLE_TOLERANCE,
+                    WARNING_COLOR.into(),
+                );
+            }
+            TelegraphKind::EdgeArrow { pos, direction } => {
+                if !is_flash_on {
+                    return Ok(());
+                }
+
+                let tip = pos + direction * ARROW_SIZE / 2.0;
+                let base = pos - direction * ARROW_SIZE / 2.0;
+                let half_width = crate::Vector::new(-direction.y, direction.x) * ARROW_SIZE / 2.0;
+
+                builder.polygon(
+                    graphics::DrawMode::fill(),
+                    &[tip, base + half_width, base - half_width],
+                    WARNING_COLOR.into(),
+                )?;
+            }
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, (crate::Point::new(0.0, 0.0),))
+    }
+}
@@ -0,0 +1,208 @@
+use {
+    crate::{Health, OnScreen},
+    ggez::graphics,
+    serde::{Deserialize, Serialize},
+    std::time::Duration,
+};
+
+/// A slow, armoured emplacement that drifts down the screen with the scenery, firing rings of
+/// bullets.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Turret {
+    pos: crate::Point,
+    movement: crate::MovementScript,
+    #[serde(skip)]
+    sprite_cache: crate::SpriteCache,
+    health: u16,
+    max_health: u16,
+    time_since_shot: Duration,
+    offset_next_ring: bool,
+    has_entered_screen: bool,
+}
+
+impl Turret {
+    const IMG_DATA: &[u8] = include_bytes!("turret.png");
+    const SPEED: f32 = 30.0;
+    const BULLET_SPEED: f32 = 1.0;
+    const SHOT_COOLDOWN: Duration = Duration::from_millis(2000);
+    const BULLETS_PER_RING: usize = 12;
+    const BULLETS_ON_DEATH: usize = 8;
+    const DEATH_BULLET_AIM_DELAY: Duration = Duration::from_millis(750);
+    const BULLET_COLOR: graphics::Color = graphics::Color::new(0.4, 0.8, 1.0, 1.0);
+    const DEATH_BULLET_COLOR: graphics::Color = graphics::Color::new(1.0, 0.6, 0.2, 1.0);
+    /// How much health the turret has on Normal.
+    const BASE_HEALTH: u16 = 15;
+
+    /// Creates a turret that follows its entry movement (if it has one) and then drifts down the
+    /// screen.
+    pub fn new(
+        pos: crate::Point,
+        entry: Option<crate::Movement>,
+        difficulty: &crate::DifficultySettings,
+    ) -> Self {
+        let movement = entry
+            .into_iter()
+            .chain(std::iter::once(crate::Movement::Linear {
+                velocity: crate::Vector::new(0.0, Self::SPEED),
+            }))
+            .collect();
+
+        let max_health = difficulty.max_health(Self::BASE_HEALTH);
+
+        Self {
+            pos,
+            movement: crate::MovementScript::new(movement),
+            sprite_cache: crate::SpriteCache::new(Self::IMG_DATA),
+            health: max_health,
+            max_health,
+            time_since_shot: Duration::from_millis(0),
+            offset_next_ring: false,
+            has_entered_screen: false,
+        }
+    }
+}
+
+impl From<Turret> for crate::SavedEnemy {
+    fn from(enemy: Turret) -> Self {
+        Self::Turret(enemy)
+    }
+}
+
+impl crate::EnemyBehaviour for Turret {
+    const SCORE_VALUE: u32 = 500;
+    const SHOWS_HEALTH_BAR: bool = true;
+
+    fn movement(&mut self, screen: graphics::Rect, delta_time: Duration) {
+        self.movement.update(&mut self.pos, delta_time);
+
+        if self.is_on_screen(screen) {
+            self.has_entered_screen = true;
+        }
+    }
+
+    fn attack(
+        &mut self,
+        _screen: graphics::Rect,
+        _rng: &mut crate::GameRng,
+        _target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+        delta_time: Duration,
+    ) -> Vec<crate::Bullet> {
+        self.time_since_shot += delta_time;
+
+        if !self.has_entered_screen || self.time_since_shot < Self::SHOT_COOLDOWN {
+            return vec![];
+        }
+
+        self.time_since_shot = Duration::from_millis(0);
+
+        let bullets_per_ring = difficulty.bullet_count(Self::BULLETS_PER_RING);
+
+        // Offset every other ring by half a gap so that the player can’t sit in the same gap
+        // forever.
+        let angle = if self.offset_next_ring {
+            std::f32::consts::PI / bullets_per_ring as f32
+        } else {
+            0.0
+        };
+        self.offset_next_ring = !self.offset_next_ring;
+
+        // The rings bounce off the edges of the screen once, so they cross back over the turret’s
+        // own fire.
+        crate::Bullet::ring(self.pos, bullets_per_ring, angle, Self::BULLET_SPEED)
+            .into_iter()
+            .map(|bullet| {
+                bullet
+                    .with_shape(crate::BulletShape::SmallRound)
+                    .with_color(Self::BULLET_COLOR)
+                    .with_edge_policy(crate::EdgePolicy::Bounce(1))
+            })
+            .collect()
+    }
+
+    fn death_behaviour(
+        &self,
+        _rng: &mut crate::GameRng,
+        target: crate::Point,
+        difficulty: &crate::DifficultySettings,
+    ) -> Vec<crate::Bullet> {
+        // Release a slow ring of bullets that all turn on the ship after a moment, to punish
+        // destroying the turret at point-blank range.
+        let to_target = target - self.pos;
+        let angle = to_target.y.atan2(to_target.x);
+
+        crate::Bullet::ring(
+            self.pos,
+            difficulty.bullet_count(Self::BULLETS_ON_DEATH),
+            angle,
+            Self::BULLET_SPEED / 2.0,
+        )
+        .into_iter()
+        .map(|bullet| {
+            bullet
+                .with_shape(crate::BulletShape::Kunai)
+                .with_color(Self::DEATH_BULLET_COLOR)
+                .with_behaviour(crate::BulletBehaviour::ChangeDirection {
+                    after: Self::DEATH_BULLET_AIM_DELAY,
+                    direction: crate::NewDirection::AtTarget,
+                    speed: Some(Self::BULLET_SPEED * 2.0),
+                })
+        })
+        .collect()
+    }
+
+    fn drops(&self, _rng: &mut crate::GameRng) -> Vec<crate::ItemKind> {
+        vec![
+            crate::ItemKind::Power,
+            crate::ItemKind::Point,
+            crate::ItemKind::Point,
+        ]
+    }
+
+    fn has_left_screen(&self, screen: graphics::Rect) -> bool {
+        self.has_entered_screen && !self.is_on_screen(screen)
+    }
+}
+
+impl crate::Position for Turret {
+    fn pos(&self) -> crate::Point {
+        self.pos
+    }
+
+    fn pos_mut(&mut self) -> &mut crate::Point {
+        &mut self.pos
+    }
+}
+
+impl Health for Turret {
+    fn max_health(&self) -> u16 {
+        self.max_health
+    }
+
+    fn health(&self) -> u16 {
+        self.health
+    }
+
+    fn health_mut(&mut self) -> &mut u16 {
+        &mut self.health
+    }
+}
+
+impl crate::TakeDamage for Turret {}
+
+impl AsRef<crate::SpriteCache> for Turret {
+    fn as_ref(&self) -> &crate::SpriteCache {
+        &self.sprite_cache
+    }
+}
+
+impl crate::RestoreSprite for Turret {
+    fn restore_sprite(&mut self) {
+        self.sprite_cache = crate::SpriteCache::new(Self::IMG_DATA);
+    }
+}
+
+impl crate::Hitbox for Turret {
+    const WIDTH_MUL: f32 = 0.7;
+    const HEIGHT_MUL: f32 = 0.8;
+}
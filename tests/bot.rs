@@ -0,0 +1,68 @@
+//! Smoke tests that the game stays beatable, by having the bot play it. The bot can’t bomb, so if
+//! it can’t get through a stage with the lives it starts with, players are unlikely to either.
+
+/// How long a stage is played for at most before giving up on it.
+const MAX_TICKS: u32 = 10 * 60 * game_test::TICKS_PER_SECOND;
+
+/// Has the bot play a practice run of every stage, with the difficulty’s usual starting lives and
+/// bombs at full power, and checks that it gets to the end of each one without letting any boss
+/// attack run out of time, which would let it through without beating the boss.
+fn check_stages_survivable(difficulty: game_test::Difficulty) {
+    let screen = game_test::PLAYFIELD;
+    let settings = difficulty.settings().expect("invalid difficulty data");
+    let bot = game_test::Bot::new(0);
+    let stage_count = game_test::Game::new(0, Default::default(), screen)
+        .stages()
+        .len();
+
+    for stage in 0..stage_count {
+        let setup = game_test::RunSetup {
+            difficulty,
+            mode: game_test::RunMode::Practice(game_test::Practice {
+                start: game_test::PracticeStart::Stage(stage),
+                lives: settings.starting_lives,
+                bombs: settings.starting_bombs,
+                power_level: game_test::ShotType::MAX_POWER_LEVEL,
+            }),
+            is_co_op: false,
+        };
+        let mut game = game_test::Game::new(0, setup, screen);
+
+        while game.outcome().is_none() && game.tick() < MAX_TICKS {
+            let input = bot.input(&game, screen);
+            game.update(&[input], screen);
+        }
+
+        let name = game.stage().name();
+
+        match game.outcome() {
+            Some(game_test::Outcome::Won) => assert!(
+                game.stats().attacks_timed_out == 0,
+                "the bot ran out of time on {} boss attacks on {}",
+                game.stats().attacks_timed_out,
+                name
+            ),
+            Some(game_test::Outcome::Died) => panic!(
+                "the bot died on {} after {} hits",
+                name,
+                game.stats().damage_taken
+            ),
+            None => panic!("the bot didn’t get to the end of {}", name),
+        }
+    }
+}
+
+#[test]
+fn stages_are_survivable_on_normal() {
+    check_stages_survivable(game_test::Difficulty::Normal);
+}
+
+#[test]
+fn stages_are_survivable_on_hard() {
+    check_stages_survivable(game_test::Difficulty::Hard);
+}
+
+#[test]
+fn stages_are_survivable_on_lunatic() {
+    check_stages_survivable(game_test::Difficulty::Lunatic);
+}
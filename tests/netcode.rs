@@ -0,0 +1,316 @@
+//! Tests that two games playing online over a bad connection stay in step, by playing both ends
+//! of a session in the same process over a pretend network, or over UDP on this machine.
+
+use {
+    game_test::{Game, GameRng, Netplay, PlayerInput, RollbackSession, Transport},
+    rand::{Rng, SeedableRng},
+    std::{
+        cell::Cell,
+        cell::RefCell,
+        io,
+        net::{SocketAddr, UdpSocket},
+        rc::Rc,
+        time::Duration,
+    },
+};
+
+const SEED: u64 = 7;
+const INPUT_DELAY: u32 = 2;
+/// How many ticks each game plays before they are compared.
+const TICKS: u32 = 600;
+/// How many frames the games get to finish in before the test gives up on them.
+const MAX_FRAMES: u32 = 20 * TICKS;
+
+/// How a pretend network treats the packets sent over it.
+#[derive(Clone, Copy)]
+struct Conditions {
+    /// The fewest and most frames a packet takes to arrive, picked at random for each packet, so
+    /// that packets arrive out of order unless the two are the same.
+    min_delay: u32,
+    max_delay: u32,
+    /// The chance of a packet being lost.
+    loss: f64,
+}
+
+/// Packets on their way one way along a link, each with the frame it arrives on.
+type InFlight = Rc<RefCell<Vec<(u32, Vec<u8>)>>>;
+
+/// One end of a pretend network between two games in the same process.
+struct Link {
+    conditions: Conditions,
+    frame: Rc<Cell<u32>>,
+    outgoing: InFlight,
+    incoming: InFlight,
+    rng: GameRng,
+}
+
+impl Link {
+    /// Both ends of a link, which go by the given frame count.
+    fn pair(conditions: Conditions, frame: &Rc<Cell<u32>>) -> (Self, Self) {
+        let a_to_b = InFlight::default();
+        let b_to_a = InFlight::default();
+
+        let end = |outgoing: &InFlight, incoming: &InFlight, seed| Self {
+            conditions,
+            frame: Rc::clone(frame),
+            outgoing: Rc::clone(outgoing),
+            incoming: Rc::clone(incoming),
+            rng: GameRng::seed_from_u64(seed),
+        };
+
+        (end(&a_to_b, &b_to_a, 1), end(&b_to_a, &a_to_b, 2))
+    }
+}
+
+impl Transport for Link {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        if self.rng.gen_bool(self.conditions.loss) {
+            return Ok(());
+        }
+
+        let delay = self
+            .rng
+            .gen_range(self.conditions.min_delay, self.conditions.max_delay + 1);
+        self.outgoing
+            .borrow_mut()
+            .push((self.frame.get() + delay, packet.to_vec()));
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let frame = self.frame.get();
+        let (arrived, in_flight): (Vec<_>, Vec<_>) = self
+            .incoming
+            .borrow_mut()
+            .drain(..)
+            .partition(|&(due, _)| due <= frame);
+        *self.incoming.borrow_mut() = in_flight;
+
+        Ok(arrived.into_iter().map(|(_, packet)| packet).collect())
+    }
+}
+
+/// One of the two games, along with where its player’s inputs come from.
+struct Peer {
+    netplay: Netplay<Box<dyn Transport>>,
+    game: Game,
+    next_input: Box<dyn FnMut() -> PlayerInput>,
+}
+
+impl Peer {
+    fn new(
+        transport: impl Transport + 'static,
+        local_player: usize,
+        next_input: Box<dyn FnMut() -> PlayerInput>,
+    ) -> Self {
+        let transport: Box<dyn Transport> = Box::new(transport);
+        let setup = game_test::RunSetup {
+            is_co_op: true,
+            ..Default::default()
+        };
+
+        Self {
+            netplay: Netplay::new(RollbackSession::new(transport, local_player, INPUT_DELAY)),
+            game: Game::new(SEED, setup, game_test::PLAYFIELD),
+            next_input,
+        }
+    }
+
+    /// Does what the game does every frame, stopping once it has played every tick.
+    fn update(&mut self) {
+        self.netplay.poll().unwrap();
+        self.netplay.roll_back(&mut self.game).unwrap();
+
+        if self.netplay.session().tick() < TICKS {
+            self.netplay.advance(&mut self.game, (self.next_input)());
+        }
+
+        self.netplay.send_pending().unwrap();
+    }
+
+    fn is_finished(&self) -> bool {
+        self.netplay.session().tick() == TICKS && self.netplay.session().is_confirmed()
+    }
+}
+
+/// A player who mashes buttons at random, changing what they are doing every few ticks.
+fn button_masher(seed: u64) -> Box<dyn FnMut() -> PlayerInput> {
+    let mut rng = GameRng::seed_from_u64(seed);
+    let mut input = PlayerInput::default();
+
+    Box::new(move || {
+        if rng.gen_bool(0.2) {
+            input = PlayerInput::from_bits(rng.gen());
+        }
+
+        input
+    })
+}
+
+fn holding(input: PlayerInput) -> Box<dyn FnMut() -> PlayerInput> {
+    Box::new(move || input)
+}
+
+fn state(game: &Game) -> String {
+    ron::ser::to_string(game).unwrap()
+}
+
+/// Plays both games until they have played every tick and heard every input, calling
+/// `on_frame` after each frame, then checks that they ended up exactly the same.
+fn play(mut peers: [Peer; 2], frame: &Rc<Cell<u32>>, mut on_frame: impl FnMut(&[Peer; 2])) {
+    while !peers.iter().all(Peer::is_finished) {
+        assert!(
+            frame.get() < MAX_FRAMES,
+            "the games got stuck at ticks {} and {}",
+            peers[0].netplay.session().tick(),
+            peers[1].netplay.session().tick()
+        );
+
+        for peer in &mut peers {
+            peer.update();
+        }
+
+        on_frame(&peers);
+        frame.set(frame.get() + 1);
+    }
+
+    assert!(
+        state(&peers[0].game) == state(&peers[1].game),
+        "the games are out of step"
+    );
+}
+
+/// Plays two button-mashing players against each other over a network like the one given.
+fn play_mashers(conditions: Conditions) {
+    let frame = Rc::new(Cell::new(0));
+    let (link_a, link_b) = Link::pair(conditions, &frame);
+    let peers = [
+        Peer::new(link_a, 0, button_masher(10)),
+        Peer::new(link_b, 1, button_masher(20)),
+    ];
+
+    play(peers, &frame, |_| ());
+}
+
+#[test]
+fn stays_in_step_with_delayed_packets() {
+    play_mashers(Conditions {
+        min_delay: 5,
+        max_delay: 5,
+        loss: 0.0,
+    });
+}
+
+#[test]
+fn stays_in_step_with_dropped_packets() {
+    play_mashers(Conditions {
+        min_delay: 1,
+        max_delay: 1,
+        loss: 0.3,
+    });
+}
+
+#[test]
+fn stays_in_step_with_reordered_packets() {
+    play_mashers(Conditions {
+        min_delay: 0,
+        max_delay: 12,
+        loss: 0.0,
+    });
+}
+
+/// Packets taking longer than the games may guess ahead make both of them wait, and losing the
+/// last packets each way while they wait mustn’t leave them waiting forever.
+#[test]
+fn recovers_from_losing_packets_while_waiting() {
+    play_mashers(Conditions {
+        min_delay: 2 * game_test::MAX_PREDICTION,
+        max_delay: 2 * game_test::MAX_PREDICTION,
+        loss: 0.8,
+    });
+}
+
+/// The second player moves from the start, so the first game guesses wrong about them until
+/// their inputs arrive, and has to roll back to put it right.
+#[test]
+fn rolls_back_wrong_guesses() {
+    const CHECKED_TICK: u32 = INPUT_DELAY + 4;
+
+    let frame = Rc::new(Cell::new(0));
+    let (link_a, link_b) = Link::pair(
+        Conditions {
+            min_delay: 6,
+            max_delay: 6,
+            loss: 0.0,
+        },
+        &frame,
+    );
+    let peers = [
+        Peer::new(link_a, 0, holding(PlayerInput::default())),
+        Peer::new(
+            link_b,
+            1,
+            holding(PlayerInput {
+                right: true,
+                shoot: true,
+                ..Default::default()
+            }),
+        ),
+    ];
+
+    // How each game first played the checked tick, before hearing from the other player.
+    let mut first_guesses = [None, None];
+
+    play(peers, &frame, |peers| {
+        for (guess, peer) in first_guesses.iter_mut().zip(peers) {
+            if guess.is_none() && peer.game.tick() == CHECKED_TICK {
+                *guess = Some(state(&peer.game));
+            }
+        }
+    });
+
+    assert!(
+        first_guesses[0] != first_guesses[1],
+        "the first game guessed right, so it never had to roll back"
+    );
+}
+
+/// Two addresses on this machine that nothing else was using a moment ago.
+fn free_local_addrs() -> [SocketAddr; 2] {
+    let sockets = [
+        UdpSocket::bind("127.0.0.1:0").unwrap(),
+        UdpSocket::bind("127.0.0.1:0").unwrap(),
+    ];
+
+    [
+        sockets[0].local_addr().unwrap(),
+        sockets[1].local_addr().unwrap(),
+    ]
+}
+
+/// Plays two button-mashing players against each other over real sockets, made worse in the same
+/// way as when trying out netplay between two games on the same machine.
+#[test]
+fn stays_in_step_over_udp_with_latency_and_loss() {
+    const FRAME_DURATION: Duration = Duration::from_millis(1);
+    const LATENCY: Duration = Duration::from_millis(20);
+    const LOSS: f64 = 0.2;
+
+    let [addr_a, addr_b] = free_local_addrs();
+    let transport = |local, peer| {
+        game_test::NetworkShim::new(
+            game_test::UdpTransport::bind(local, peer).unwrap(),
+            LATENCY,
+            LOSS,
+        )
+    };
+    let peers = [
+        Peer::new(transport(addr_a, addr_b), 0, button_masher(10)),
+        Peer::new(transport(addr_b, addr_a), 1, button_masher(20)),
+    ];
+
+    play(peers, &Rc::new(Cell::new(0)), |_| {
+        std::thread::sleep(FRAME_DURATION)
+    });
+}
@@ -0,0 +1,53 @@
+//! Tests that replays play back the runs they were recorded from, and notice when they don’t.
+
+/// How long the run is recorded for.
+const TICKS: u32 = 20 * game_test::TICKS_PER_SECOND;
+
+/// Has the bot play the start of a run, recording it as it goes.
+fn record() -> game_test::Replay {
+    let screen = game_test::PLAYFIELD;
+    let bot = game_test::Bot::new(0);
+    let mut game = game_test::Game::new(0, Default::default(), screen);
+    let mut replay = game_test::Replay::new(&game);
+
+    while game.tick() < TICKS {
+        let inputs = vec![bot.input(&game, screen)];
+        replay.record(&game, inputs.clone());
+        game.update(&inputs, screen);
+    }
+
+    replay
+}
+
+/// Plays a replay back from the start, stopping at the first tick it has gone out of step on.
+fn play_back(replay: &game_test::Replay) -> anyhow::Result<()> {
+    let mut game = replay.start();
+
+    for inputs in &replay.inputs {
+        replay.check(&game)?;
+        game.update(inputs, game_test::PLAYFIELD);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn replays_stay_in_step() {
+    play_back(&record()).unwrap();
+}
+
+/// Leaves the ship idle for the whole replay, so that it scores nothing and the rank falls behind
+/// the recorded one.
+#[test]
+fn replays_with_other_inputs_go_out_of_step() {
+    let mut replay = record();
+
+    for inputs in &mut replay.inputs {
+        *inputs = vec![Default::default()];
+    }
+
+    assert!(
+        play_back(&replay).is_err(),
+        "the edited replay stayed in step"
+    );
+}
@@ -0,0 +1,57 @@
+//! Tests that a saved game carries on exactly as it would have if it had never been saved.
+
+/// How many ticks the game is played for before it is saved, and then on for once it has been.
+const TICKS_BEFORE_SAVE: u32 = 3 * game_test::TICKS_PER_SECOND;
+const TICKS_AFTER_SAVE: u32 = 2 * game_test::TICKS_PER_SECOND;
+
+fn state(game: &game_test::Game) -> String {
+    ron::ser::to_string(game).unwrap()
+}
+
+/// Saves a game in the middle of the Sky Core’s last phase, which fills the screen with tinted
+/// bullets, and checks that the loaded game plays on the same as the original.
+#[test]
+fn saved_games_carry_on_the_same() {
+    let screen = game_test::PLAYFIELD;
+    let stages = game_test::Game::new(0, Default::default(), screen).stages();
+    let start = *game_test::PracticeStart::all(stages)
+        .last()
+        .expect("no practice starts");
+    let setup = game_test::RunSetup {
+        mode: game_test::RunMode::Practice(game_test::Practice {
+            start,
+            lives: 8,
+            bombs: 0,
+            power_level: game_test::ShotType::MAX_POWER_LEVEL,
+        }),
+        ..Default::default()
+    };
+    let mut game = game_test::Game::new(0, setup, screen);
+
+    for _ in 0..TICKS_BEFORE_SAVE {
+        game.update(&[Default::default()], screen);
+    }
+
+    assert!(!game.bullets().is_empty(), "there are no bullets to save");
+
+    let path = std::env::temp_dir().join(format!("game-test-snapshot-{}.ron", std::process::id()));
+    game.save(&path).unwrap();
+    let loaded = game_test::Game::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let mut loaded = loaded.unwrap();
+
+    assert!(
+        state(&game) == state(&loaded),
+        "the loaded game is different"
+    );
+
+    for _ in 0..TICKS_AFTER_SAVE {
+        game.update(&[Default::default()], screen);
+        loaded.update(&[Default::default()], screen);
+    }
+
+    assert!(
+        state(&game) == state(&loaded),
+        "the loaded game played on differently"
+    );
+}